@@ -1,26 +1,74 @@
+//! Look up a crate in the local index.
+//!
+//! Usage: `cargo run --example test_index [crate] [index-dir]`
+//!
+//! With `index-dir`, the entry is read from a directory laid out like a sparse
+//! index (`3/s/syn`, `se/rd/serde`, ...), the same layout `--index-path` expects.
+//! Otherwise Cargo's own sparse index cache under `$CARGO_HOME` is used.
+
 fn main() {
     let url = "sparse+https://index.crates.io/";
-    let home = std::path::PathBuf::from("/Users/john/.cargo");
-    
+    let mut args = std::env::args().skip(1);
+    let name = args.next().unwrap_or_else(|| "anyhow".to_string());
+
+    if let Some(dir) = args.next() {
+        println!("=== Index directory ===");
+        let path = std::path::Path::new(&dir).join(relative_path(&name));
+        match crates_index::Crate::new(&path) {
+            Ok(k) => println!(
+                "{name} -> {:?}",
+                k.highest_normal_version().map(|v| v.version())
+            ),
+            Err(e) => println!("{name} error ({}): {e}", path.display()),
+        }
+        return;
+    }
+
+    let home = std::env::var("CARGO_HOME").map_or_else(
+        |_| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+            std::path::PathBuf::from(home).join(".cargo")
+        },
+        std::path::PathBuf::from,
+    );
+
     println!("=== Legacy hash ===");
     if let Ok(idx) = crates_index::SparseIndex::new_cargo_default() {
-        match idx.crate_from_cache("anyhow") {
-            Ok(k) => println!("anyhow -> {:?}", k.highest_normal_version().map(|v| v.version())),
-            Err(e) => println!("anyhow error: {e}"),
+        match idx.crate_from_cache(&name) {
+            Ok(k) => println!(
+                "{name} -> {:?}",
+                k.highest_normal_version().map(|v| v.version())
+            ),
+            Err(e) => println!("{name} error: {e}"),
         }
     } else {
         println!("Failed to create legacy index");
     }
-    
+
     println!("=== Stable hash ===");
     if let Ok(idx) = crates_index::SparseIndex::with_path_and_hash_kind(
-        &home, url, &crates_index::HashKind::Stable
+        &home,
+        url,
+        &crates_index::HashKind::Stable,
     ) {
-        match idx.crate_from_cache("anyhow") {
-            Ok(k) => println!("anyhow -> {:?}", k.highest_normal_version().map(|v| v.version())),
-            Err(e) => println!("anyhow error: {e}"),
+        match idx.crate_from_cache(&name) {
+            Ok(k) => println!(
+                "{name} -> {:?}",
+                k.highest_normal_version().map(|v| v.version())
+            ),
+            Err(e) => println!("{name} error: {e}"),
         }
     } else {
         println!("Failed to create stable index");
     }
 }
+
+fn relative_path(name: &str) -> String {
+    let name = name.to_ascii_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
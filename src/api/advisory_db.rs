@@ -0,0 +1,333 @@
+use anyhow::Result;
+use log::{debug, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::types::{AdvisoryMatch, AdvisorySeverity, PublishedVersion};
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// Advisories from a local clone of the `RustSec` advisory database, which keeps
+/// one file per advisory under `crates/<name>/RUSTSEC-*.md`.
+pub struct AdvisoryDb {
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+struct Advisory {
+    id: String,
+    severity: AdvisorySeverity,
+    patched: Vec<VersionReq>,
+    unaffected: Vec<VersionReq>,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryFile {
+    advisory: AdvisoryHeader,
+    #[serde(default)]
+    versions: VersionRanges,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryHeader {
+    id: String,
+    #[serde(default)]
+    cvss: Option<String>,
+    /// `unmaintained`, `unsound` or `notice` for advisories that are not vulnerabilities
+    #[serde(default)]
+    informational: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Default, Deserialize)]
+struct VersionRanges {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+impl AdvisoryDb {
+    /// Load the advisories of the given crates. Withdrawn advisories are skipped.
+    pub fn load(path: &Path, crate_names: &[String]) -> Result<Self> {
+        let crates_dir = path.join("crates");
+        if !crates_dir.is_dir() {
+            anyhow::bail!(
+                "Not a RustSec advisory-db checkout (no crates/ directory): {}",
+                path.display()
+            );
+        }
+
+        let advisories: HashMap<String, Vec<Advisory>> = crate_names
+            .iter()
+            .map(|name| (name.clone(), read_crate_advisories(&crates_dir.join(name))))
+            .filter(|(_, advisories)| !advisories.is_empty())
+            .collect();
+        debug!(
+            "Loaded {} advisories for {} crates from {}",
+            advisories.values().map(Vec::len).sum::<usize>(),
+            advisories.len(),
+            path.display()
+        );
+
+        Ok(AdvisoryDb { advisories })
+    }
+
+    /// Advisories affecting `version` of `name`. `published` lets the fixed
+    /// version be an actual release rather than the bound of a patched range.
+    pub fn check(
+        &self,
+        name: &str,
+        version: &str,
+        published: Option<&[PublishedVersion]>,
+    ) -> Vec<AdvisoryMatch> {
+        let Some(version) = Version::parse(version) else {
+            return Vec::new();
+        };
+        self.advisories
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|advisory| advisory.affects(&version))
+            .map(|advisory| AdvisoryMatch {
+                id: advisory.id.clone(),
+                severity: advisory.severity.clone(),
+                fixed_in: advisory.fixed_in(&version, published),
+            })
+            .collect()
+    }
+}
+
+impl Advisory {
+    fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| req.matches(version))
+    }
+
+    /// Lowest version above `version` that this advisory no longer affects.
+    fn fixed_in(
+        &self,
+        version: &Version,
+        published: Option<&[PublishedVersion]>,
+    ) -> Option<String> {
+        let candidates: Vec<Version> = match published {
+            Some(published) => published
+                .iter()
+                .filter(|v| !v.yanked)
+                .filter_map(|v| Version::parse(&v.num))
+                .filter(|v| v.pre_release.is_none())
+                .collect(),
+            None => self
+                .patched
+                .iter()
+                .filter_map(VersionReq::lower_bound)
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate > version && !self.affects(candidate))
+            .min()
+            .map(|fixed| fixed.to_string())
+    }
+}
+
+fn read_crate_advisories(dir: &Path) -> Vec<Advisory> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "md" || ext == "toml")
+        })
+        .filter_map(|path| match read_advisory(&path) {
+            Ok(advisory) => advisory,
+            Err(e) => {
+                warn!("Skipping advisory {}: {e}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse one advisory file: TOML, or Markdown with the TOML in a leading
+/// ```` ```toml ```` block. Withdrawn advisories yield `None`.
+fn read_advisory(path: &Path) -> Result<Option<Advisory>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_advisory(&content, path.extension().is_some_and(|ext| ext == "md"))
+}
+
+fn parse_advisory(content: &str, markdown: bool) -> Result<Option<Advisory>> {
+    let toml = if markdown {
+        front_matter(content).ok_or_else(|| anyhow::anyhow!("no ```toml front matter"))?
+    } else {
+        content
+    };
+
+    let file: AdvisoryFile = toml::from_str(toml)?;
+    if file.advisory.withdrawn.is_some() {
+        return Ok(None);
+    }
+
+    let parse_ranges = |ranges: &[String]| -> Result<Vec<VersionReq>> {
+        ranges
+            .iter()
+            .map(|range| {
+                VersionReq::parse(range)
+                    .ok_or_else(|| anyhow::anyhow!("invalid version range '{range}'"))
+            })
+            .collect()
+    };
+
+    let severity = match (&file.advisory.informational, &file.advisory.cvss) {
+        (Some(kind), _) => AdvisorySeverity::Informational(kind.clone()),
+        (None, Some(vector)) => cvss_severity(vector),
+        (None, None) => AdvisorySeverity::Unrated,
+    };
+
+    Ok(Some(Advisory {
+        patched: parse_ranges(&file.versions.patched)?,
+        unaffected: parse_ranges(&file.versions.unaffected)?,
+        id: file.advisory.id,
+        severity,
+    }))
+}
+
+fn front_matter(content: &str) -> Option<&str> {
+    let rest = content.trim_start().strip_prefix("```toml")?;
+    let end = rest.find("\n```")?;
+    Some(&rest[..end])
+}
+
+/// Severity band of a CVSS v3 vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`.
+fn cvss_severity(vector: &str) -> AdvisorySeverity {
+    match cvss_base_score(vector) {
+        Some(score) if score >= 9.0 => AdvisorySeverity::Critical,
+        Some(score) if score >= 7.0 => AdvisorySeverity::High,
+        Some(score) if score >= 4.0 => AdvisorySeverity::Medium,
+        Some(_) => AdvisorySeverity::Low,
+        None => AdvisorySeverity::Unrated,
+    }
+}
+
+/// CVSS v3.x base score, following the formulas of the specification.
+fn cvss_base_score(vector: &str) -> Option<f64> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: HashMap<&str, &str> = parts.filter_map(|part| part.split_once(':')).collect();
+    let scope_changed = match *metrics.get("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match *metrics.get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges = match (*metrics.get("PR")?, scope_changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let user_interaction = match *metrics.get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_of = |metric: &str| -> Option<f64> {
+        match *metrics.get(metric)? {
+            "H" => Some(0.56),
+            "L" => Some(0.22),
+            "N" => Some(0.0),
+            _ => None,
+        }
+    };
+
+    let iss = 1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+    let impact = if scope_changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * user_interaction;
+    let score = if scope_changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)))
+}
+
+/// The specification's "round up to one decimal", robust to floating point noise.
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round();
+    if scaled % 10_000.0 == 0.0 {
+        scaled / 100_000.0
+    } else {
+        ((scaled / 10_000.0).floor() + 1.0) / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cvss_base_score() {
+        let score = |vector| cvss_base_score(vector).unwrap();
+        assert!((score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H") - 9.8).abs() < 1e-9);
+        assert!((score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:C/C:H/I:H/A:H") - 10.0).abs() < 1e-9);
+        assert!((score("CVSS:3.0/AV:L/AC:L/PR:L/UI:N/S:U/C:H/I:N/A:N") - 5.5).abs() < 1e-9);
+        assert!((score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N")).abs() < 1e-9);
+        assert_eq!(cvss_base_score("CVSS:4.0/AV:N/AC:L"), None);
+    }
+
+    #[test]
+    fn test_parse_advisory() {
+        let content = "```toml\n[advisory]\nid = \"RUSTSEC-2099-0001\"\npackage = \"demo\"\n\
+            cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H\"\n\n\
+            [versions]\npatched = [\">= 1.4.2\", \">= 1.3.7, < 1.4.0\"]\nunaffected = [\"< 1.0.0\"]\n```\n\n# Demo\n";
+        let advisory = parse_advisory(content, true).unwrap().unwrap();
+        assert_eq!(advisory.id, "RUSTSEC-2099-0001");
+        assert_eq!(advisory.severity, AdvisorySeverity::Critical);
+        let affects = |v| advisory.affects(&Version::parse(v).unwrap());
+        assert!(affects("1.3.6"));
+        assert!(!affects("1.3.7"));
+        assert!(affects("1.4.0"));
+        assert!(!affects("1.4.2"));
+        assert!(!affects("0.9.0"));
+
+        let version = Version::parse("1.3.2").unwrap();
+        assert_eq!(advisory.fixed_in(&version, None), Some("1.3.7".to_string()));
+
+        let withdrawn = "[advisory]\nid = \"RUSTSEC-2099-0002\"\npackage = \"demo\"\n\
+            withdrawn = \"2099-01-02\"\n\n[versions]\npatched = []\n";
+        assert!(parse_advisory(withdrawn, false).unwrap().is_none());
+        assert!(parse_advisory("# No front matter\n", true).is_err());
+    }
+}
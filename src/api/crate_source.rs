@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// A release's source as Cargo keeps it after a build or `cargo fetch`.
+#[derive(Debug, Clone)]
+pub enum CrateSource {
+    /// Unpacked under `$CARGO_HOME/registry/src/<registry>/<name>-<version>`
+    Unpacked(PathBuf),
+    /// The downloaded `$CARGO_HOME/registry/cache/<registry>/<name>-<version>.crate`
+    Archive(PathBuf),
+}
+
+impl CrateSource {
+    /// Look for `name` `version` in Cargo's crates.io caches, preferring unpacked
+    /// sources. Other registries are skipped, as a package of the same name there
+    /// is not the crates.io release; nothing is downloaded.
+    pub fn find(name: &str, version: &str) -> Option<Self> {
+        let registry = crate::utils::cargo_home().join("registry");
+        let package = format!("{name}-{version}");
+        let registries = |kind: &str| {
+            fs::read_dir(registry.join(kind))
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|dir| crate::utils::is_crates_io_registry(dir))
+        };
+
+        registries("src")
+            .map(|dir| dir.join(&package))
+            .find(|path| path.is_dir())
+            .map(CrateSource::Unpacked)
+            .or_else(|| {
+                registries("cache")
+                    .map(|dir| dir.join(format!("{package}.crate")))
+                    .find(|path| path.is_file())
+                    .map(CrateSource::Archive)
+            })
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            CrateSource::Unpacked(path) | CrateSource::Archive(path) => path,
+        }
+    }
+
+    /// A writable copy of the package under `dir`, unpacked (or copied) on first
+    /// use, so tools that build it never write into Cargo's registry.
+    pub fn unpack_into(&self, dir: &Path) -> Result<PathBuf> {
+        let file_name = self
+            .path()
+            .file_name()
+            .context("source path has no file name")?
+            .to_string_lossy();
+        let package = dir.join(file_name.trim_end_matches(".crate"));
+        if package.is_dir() {
+            return Ok(package);
+        }
+
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        match self {
+            CrateSource::Unpacked(source) => copy_dir(source, &package)?,
+            CrateSource::Archive(path) => {
+                let file = File::open(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                tar::Archive::new(GzDecoder::new(BufReader::new(file)))
+                    .unpack(dir)
+                    .with_context(|| format!("Failed to unpack {}", path.display()))?;
+            }
+        }
+        Ok(package)
+    }
+
+    /// The first file at the package root whose name matches one of `candidates`
+    /// (ignoring case), with its content.
+    pub fn read_root_file(&self, candidates: &[&str]) -> Result<Option<(String, String)>> {
+        let wanted = |file_name: &str| {
+            candidates
+                .iter()
+                .position(|candidate| candidate.eq_ignore_ascii_case(file_name))
+        };
+
+        let mut found: Vec<(usize, String, String)> = Vec::new();
+        match self {
+            CrateSource::Unpacked(dir) => {
+                for entry in fs::read_dir(dir)
+                    .with_context(|| format!("Failed to read {}", dir.display()))?
+                    .filter_map(Result::ok)
+                {
+                    let file_name = entry.file_name().to_string_lossy().to_string();
+                    if let Some(rank) = wanted(&file_name) {
+                        let content = fs::read_to_string(entry.path()).with_context(|| {
+                            format!("Failed to read {}", entry.path().display())
+                        })?;
+                        found.push((rank, file_name, content));
+                    }
+                }
+            }
+            CrateSource::Archive(path) => {
+                let file = File::open(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                let mut archive = tar::Archive::new(GzDecoder::new(BufReader::new(file)));
+                for entry in archive.entries()? {
+                    let mut entry = entry?;
+                    // Entries are `<name>-<version>/<path>`; only the root level counts
+                    let entry_path = entry.path()?.into_owned();
+                    let mut components = entry_path.components().skip(1);
+                    let (Some(file_name), None) = (components.next(), components.next()) else {
+                        continue;
+                    };
+                    let file_name = file_name.as_os_str().to_string_lossy().to_string();
+                    if let Some(rank) = wanted(&file_name) {
+                        let mut content = String::new();
+                        entry.read_to_string(&mut content)?;
+                        found.push((rank, file_name, content));
+                    }
+                }
+            }
+        }
+        Ok(found
+            .into_iter()
+            .min_by_key(|(rank, _, _)| *rank)
+            .map(|(_, file_name, content)| (file_name, content)))
+    }
+
+    /// The `license` the package's manifest declares, or its `license-file` for
+    /// packages that ship their own terms.
+    pub fn license(&self) -> Result<Option<String>> {
+        let Some((_, content)) = self.read_root_file(&["Cargo.toml"])? else {
+            return Ok(None);
+        };
+        let manifest: toml::Value = toml::from_str(&content).with_context(|| {
+            format!("Failed to parse the manifest in {}", self.path().display())
+        })?;
+        let package = manifest.get("package");
+        let field = |key: &str| {
+            package
+                .and_then(|p| p.get(key))
+                .and_then(toml::Value::as_str)
+        };
+        Ok(field("license")
+            .map(str::to_string)
+            .or_else(|| field("license-file").map(|file| format!("custom ({file})"))))
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("Failed to create {}", to.display()))?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+    use flate2::{Compression, write::GzEncoder};
+
+    const CHANGELOGS: [&str; 3] = ["CHANGELOG.md", "CHANGES.md", "RELEASES.md"];
+
+    /// A `.crate` archive holding `files` under `demo-1.0.0/`.
+    fn archive(dir: &Path, files: &[(&str, &str)]) -> CrateSource {
+        let path = dir.join("demo-1.0.0.crate");
+        let encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("demo-1.0.0/{name}"),
+                    content.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        CrateSource::Archive(path)
+    }
+
+    #[test]
+    fn test_read_root_file_unpacked() {
+        let dir = TempDir::new("crate-source-unpacked");
+        let package = dir.path().join("demo-1.0.0");
+        fs::create_dir_all(package.join("docs")).unwrap();
+        fs::write(package.join("releases.md"), "releases").unwrap();
+        fs::write(package.join("CHANGES.md"), "changes").unwrap();
+        fs::write(package.join("docs/CHANGELOG.md"), "nested").unwrap();
+        fs::write(package.join("Cargo.toml"), "[package]\nlicense = \"MIT\"\n").unwrap();
+        let source = CrateSource::Unpacked(package);
+
+        // The best-ranked candidate at the root, whatever the directory order
+        assert_eq!(
+            source.read_root_file(&CHANGELOGS).unwrap(),
+            Some(("CHANGES.md".to_string(), "changes".to_string()))
+        );
+        assert_eq!(source.read_root_file(&["README.md"]).unwrap(), None);
+        assert_eq!(source.license().unwrap().as_deref(), Some("MIT"));
+    }
+
+    #[test]
+    fn test_read_root_file_archive() {
+        let dir = TempDir::new("crate-source-archive");
+        let source = archive(
+            dir.path(),
+            &[
+                ("RELEASES.md", "releases"),
+                ("docs/CHANGELOG.md", "nested"),
+                ("changes.md", "changes"),
+                ("Cargo.toml", "[package]\nlicense-file = \"LICENSE.txt\"\n"),
+            ],
+        );
+
+        // Nested files never count, even when they rank higher; case does not matter
+        assert_eq!(
+            source.read_root_file(&CHANGELOGS).unwrap(),
+            Some(("changes.md".to_string(), "changes".to_string()))
+        );
+        assert_eq!(
+            source.license().unwrap().as_deref(),
+            Some("custom (LICENSE.txt)")
+        );
+    }
+
+    #[test]
+    fn test_unpack_into_reuses_existing_copy() {
+        let dir = TempDir::new("crate-source-unpack");
+        let source = archive(dir.path(), &[("src/lib.rs", "pub fn demo() {}\n")]);
+        let work = dir.path().join("work");
+
+        let package = source.unpack_into(&work).unwrap();
+        assert_eq!(package, work.join("demo-1.0.0"));
+        assert!(package.join("src/lib.rs").is_file());
+
+        // A second call keeps the copy, including edits a tool made to it
+        fs::write(package.join("src/lib.rs"), "edited").unwrap();
+        assert_eq!(source.unpack_into(&work).unwrap(), package);
+        assert_eq!(
+            fs::read_to_string(package.join("src/lib.rs")).unwrap(),
+            "edited"
+        );
+
+        let copied = CrateSource::Unpacked(package.clone())
+            .unpack_into(&dir.path().join("copy"))
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(copied.join("src/lib.rs")).unwrap(),
+            "edited"
+        );
+    }
+}
@@ -1,13 +1,19 @@
 use anyhow::Result;
 use log::{debug, warn};
 use reqwest::Client;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::types::CrateInfo;
+use crate::types::{CrateInfo, LookupError, PublishedVersion};
 
 const MAX_RETRIES: u32 = 2;
 const RETRY_DELAY_MS: u64 = 500;
 
+/// Base URL of the crates.io web API.
+pub const DEFAULT_API_URL: &str = "https://crates.io/api/v1";
+
 pub fn create_client() -> Result<Client> {
     Ok(Client::builder()
         .user_agent("cargo-stale/0.1.6")
@@ -18,48 +24,137 @@ pub fn create_client() -> Result<Client> {
         .build()?)
 }
 
-pub async fn get_latest_version(client: &Client, crate_name: &str) -> Option<String> {
+/// Fetch every published version of a crate, including yanked ones, with who
+/// published each.
+pub async fn get_published_versions(
+    client: &Client,
+    api_url: &str,
+    crate_name: &str,
+) -> Result<Vec<PublishedVersion>, LookupError> {
     let crate_name = crate_name.split_whitespace().next().unwrap_or(crate_name);
-    let url = format!("https://crates.io/api/v1/crates/{crate_name}");
+    fetch_json::<CrateInfo>(
+        client,
+        &format!("{api_url}/crates/{crate_name}"),
+        crate_name,
+    )
+    .await
+    .map(|info| info.versions)
+}
+
+#[derive(Deserialize)]
+struct VersionRecord {
+    version: PublishedVersion,
+}
+
+/// The license of a single release, from the crate's versions endpoint.
+pub async fn get_version_license(
+    client: &Client,
+    api_url: &str,
+    crate_name: &str,
+    version: &str,
+) -> Result<Option<String>, LookupError> {
+    let url = format!("{api_url}/crates/{crate_name}/{version}");
+    fetch_json::<VersionRecord>(client, &url, crate_name)
+        .await
+        .map(|record| record.version.license)
+}
+
+#[derive(Deserialize)]
+struct OwnersRecord {
+    users: Vec<User>,
+}
+
+#[derive(Deserialize)]
+struct User {
+    login: String,
+}
+
+/// Who published each release of a crate, for version lists read from an index,
+/// which does not record it. Releases from before crates.io recorded publishers
+/// map to `None`.
+pub async fn get_publishers(
+    client: &Client,
+    api_url: &str,
+    crate_name: &str,
+) -> Result<HashMap<String, Option<String>>, LookupError> {
+    let versions = get_published_versions(client, api_url, crate_name).await?;
+    Ok(publishers(&versions))
+}
+
+/// The publisher of each release in `versions`, by version number.
+pub fn publishers(versions: &[PublishedVersion]) -> HashMap<String, Option<String>> {
+    versions
+        .iter()
+        .map(|release| (release.num.clone(), release.published_by.clone()))
+        .collect()
+}
 
+/// The logins of a crate's owners, users and teams (`github:org:team`), sorted.
+pub async fn get_owners(
+    client: &Client,
+    api_url: &str,
+    crate_name: &str,
+) -> Result<Vec<String>, LookupError> {
+    let url = format!("{api_url}/crates/{crate_name}/owners");
+    let record = fetch_json::<OwnersRecord>(client, &url, crate_name).await?;
+    let mut owners: Vec<String> = record.users.into_iter().map(|user| user.login).collect();
+    owners.sort();
+    Ok(owners)
+}
+
+/// Fetch an API record about `crate_name`, retrying rate limits, server errors
+/// and connection failures. The error is the reason the last attempt failed.
+async fn fetch_json<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    crate_name: &str,
+) -> Result<T, LookupError> {
+    let mut last_error = LookupError::Timeout;
     for attempt in 0..=MAX_RETRIES {
         if attempt > 0 {
             debug!("Retry {attempt}/{MAX_RETRIES} for crate '{crate_name}'");
             tokio::time::sleep(Duration::from_millis(RETRY_DELAY_MS * u64::from(attempt))).await;
         }
 
-        match client.get(&url).send().await {
+        match client.get(url).send().await {
             Ok(response) => {
                 let status = response.status();
                 if status.is_success() {
-                    match response.json::<CrateInfo>().await {
-                        Ok(info) => return Some(info.crate_info.max_version),
-                        Err(e) => {
-                            warn!("Failed to parse response for crate '{crate_name}': {e}");
-                            return None;
-                        }
-                    }
+                    return response.json::<T>().await.map_err(|e| {
+                        warn!("Failed to parse response for crate '{crate_name}': {e}");
+                        LookupError::InvalidResponse(e.to_string())
+                    });
+                } else if status.as_u16() == 404 {
+                    return Err(LookupError::NotFound);
                 } else if status.as_u16() == 429 {
                     warn!("Rate limited (429) for crate '{crate_name}', retrying...");
+                    last_error = LookupError::RateLimited;
                     continue;
                 } else if status.is_server_error() {
                     warn!("Server error {status} for crate '{crate_name}', retrying...");
+                    last_error = LookupError::Http(status.as_u16());
                     continue;
                 }
                 warn!("HTTP {status} for crate '{crate_name}'");
-                return None;
+                return Err(LookupError::Http(status.as_u16()));
             }
             Err(e) => {
-                if e.is_timeout() || e.is_connect() {
+                if e.is_timeout() {
+                    warn!("Request for crate '{crate_name}' timed out, retrying...");
+                    last_error = LookupError::Timeout;
+                    continue;
+                }
+                if e.is_connect() {
                     warn!("Connection error for crate '{crate_name}': {e}, retrying...");
+                    last_error = LookupError::Network(e.to_string());
                     continue;
                 }
                 warn!("Request failed for crate '{crate_name}': {e}");
-                return None;
+                return Err(LookupError::Network(e.to_string()));
             }
         }
     }
 
     warn!("All retries exhausted for crate '{crate_name}'");
-    None
+    Err(last_error)
 }
@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::types::{CrateDetails, CrateInfo, PublishedVersion};
+use crate::version::comparison::select_latest;
+use crate::version::core::Version;
+
+const CACHE_FORMAT: u32 = 2;
+
+/// Identifies the dump a cache was built from, so a newer download triggers a rebuild.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheStamp {
+    format: u32,
+    source: PathBuf,
+    len: u64,
+    modified: u64,
+}
+
+/// An indexed cache of a crates.io database dump (`db-dump.tar.gz`).
+///
+/// The dump is ingested once into buckets keyed by the first two characters of
+/// the crate name, so a run only decompresses the few buckets it needs.
+pub struct DbDump {
+    dir: PathBuf,
+}
+
+impl DbDump {
+    /// Open the cache for `dump_path`, (re)building it when missing or stale.
+    pub fn open(dump_path: &Path, verbose: bool) -> Result<Self> {
+        let dump_path = dump_path
+            .canonicalize()
+            .with_context(|| format!("Database dump not found: {}", dump_path.display()))?;
+        let metadata = fs::metadata(&dump_path)?;
+        let stamp = CacheStamp {
+            format: CACHE_FORMAT,
+            source: dump_path.clone(),
+            len: metadata.len(),
+            modified: metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        };
+
+        let dir = crate::utils::cache_dir().join("db-dump");
+        let current = fs::read(dir.join("stamp.json"))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheStamp>(&bytes).ok());
+        if current.as_ref() == Some(&stamp) {
+            debug!("Using db-dump cache at {}", dir.display());
+            return Ok(DbDump { dir });
+        }
+
+        println!(
+            "🗄️  Ingesting {} (one-time, this can take a minute)...",
+            dump_path.display()
+        );
+        let buckets = ingest(&dump_path)?;
+        write_cache(&dir, &stamp, &buckets)?;
+        if verbose {
+            println!(
+                "🗄️  Cached {} crates in {}",
+                buckets.values().map(BTreeMap::len).sum::<usize>(),
+                dir.display()
+            );
+        }
+        Ok(DbDump { dir })
+    }
+
+    /// Look up the given crates; crates missing from the dump are left out.
+    pub fn lookup(&self, crate_names: &[String]) -> HashMap<String, CrateInfo> {
+        let mut by_bucket: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+        for name in crate_names {
+            by_bucket.entry(bucket_key(name)).or_default().push(name);
+        }
+
+        let mut results = HashMap::new();
+        for (key, names) in by_bucket {
+            let path = self.dir.join("buckets").join(format!("{key}.json.gz"));
+            let bucket: BTreeMap<String, CrateInfo> = match read_gz_json(&path) {
+                Ok(bucket) => bucket,
+                Err(e) => {
+                    debug!("db-dump bucket {key} unavailable: {e}");
+                    continue;
+                }
+            };
+            for name in names {
+                if let Some(info) = bucket.get(&name.to_ascii_lowercase()) {
+                    results.insert(name.clone(), info.clone());
+                }
+            }
+        }
+        results
+    }
+}
+
+fn bucket_key(name: &str) -> String {
+    name.to_ascii_lowercase().chars().take(2).collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateRow {
+    id: u64,
+    name: String,
+    #[serde(default)]
+    repository: String,
+    #[serde(default)]
+    description: String,
+    /// Only present in older dumps; newer ones use `crate_downloads.csv`
+    #[serde(default)]
+    downloads: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateDownloadsRow {
+    crate_id: u64,
+    downloads: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionRow {
+    crate_id: u64,
+    num: String,
+    created_at: String,
+    #[serde(default)]
+    license: String,
+    yanked: String,
+    #[serde(default)]
+    rust_version: String,
+}
+
+/// Stream the dump once, joining crates, download counts and versions by crate id.
+fn ingest(dump_path: &Path) -> Result<BTreeMap<String, BTreeMap<String, CrateInfo>>> {
+    let file = File::open(dump_path)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(BufReader::new(file)));
+
+    let mut crates: HashMap<u64, CrateRow> = HashMap::new();
+    let mut downloads: HashMap<u64, u64> = HashMap::new();
+    let mut versions: HashMap<u64, Vec<PublishedVersion>> = HashMap::new();
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.into_owned();
+        let in_data_dir = path
+            .parent()
+            .and_then(Path::file_name)
+            .is_some_and(|dir| dir == "data");
+        if !in_data_dir {
+            continue;
+        }
+
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("crates.csv") => read_csv(entry, |row: CrateRow| {
+                crates.insert(row.id, row);
+            })?,
+            Some("crate_downloads.csv") => read_csv(entry, |row: CrateDownloadsRow| {
+                downloads.insert(row.crate_id, row.downloads);
+            })?,
+            Some("versions.csv") => read_csv(entry, |row: VersionRow| {
+                versions
+                    .entry(row.crate_id)
+                    .or_default()
+                    .push(PublishedVersion {
+                        num: row.num,
+                        yanked: row.yanked == "t",
+                        created_at: Some(row.created_at),
+                        license: Some(row.license).filter(|l| !l.is_empty()),
+                        rust_version: Some(row.rust_version).filter(|v| !v.is_empty()),
+                        features: None,
+                        default_features: None,
+                        published_by: None,
+                    });
+            })?,
+            _ => {}
+        }
+    }
+
+    if crates.is_empty() {
+        anyhow::bail!(
+            "No data/crates.csv found in {}; is this a crates.io db-dump?",
+            dump_path.display()
+        );
+    }
+
+    let mut buckets: BTreeMap<String, BTreeMap<String, CrateInfo>> = BTreeMap::new();
+    for (id, row) in crates {
+        let versions = versions.remove(&id).unwrap_or_default();
+        // Crates with only pre-releases or yanked versions still report their newest one
+        let max_version = select_latest(&versions, false)
+            .or_else(|| {
+                versions
+                    .iter()
+                    .filter_map(|v| Version::parse(&v.num).map(|parsed| (parsed, v.num.as_str())))
+                    .max_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(_, num)| num)
+            })
+            .unwrap_or_default()
+            .to_string();
+        let info = CrateInfo {
+            crate_info: CrateDetails {
+                max_version,
+                downloads: downloads.get(&id).copied().or(row.downloads),
+                repository: Some(row.repository).filter(|r| !r.is_empty()),
+                description: Some(row.description).filter(|d| !d.is_empty()),
+            },
+            versions,
+        };
+        buckets
+            .entry(bucket_key(&row.name))
+            .or_default()
+            .insert(row.name.to_ascii_lowercase(), info);
+    }
+
+    Ok(buckets)
+}
+
+fn read_csv<R: Read, T: DeserializeOwned>(reader: R, mut on_row: impl FnMut(T)) -> Result<()> {
+    let mut reader = csv::Reader::from_reader(reader);
+    reader
+        .headers()
+        .context("Failed to read db-dump CSV header")?;
+    for row in reader.deserialize() {
+        match row {
+            Ok(row) => on_row(row),
+            Err(e) => warn!("Skipping malformed db-dump row: {e}"),
+        }
+    }
+    Ok(())
+}
+
+/// Write the buckets next to the final location and swap them in, so an
+/// interrupted ingest never leaves a half-written cache behind.
+fn write_cache(
+    dir: &Path,
+    stamp: &CacheStamp,
+    buckets: &BTreeMap<String, BTreeMap<String, CrateInfo>>,
+) -> Result<()> {
+    let staging = dir.with_extension("tmp");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(staging.join("buckets"))
+        .with_context(|| format!("Failed to create cache directory: {}", staging.display()))?;
+
+    for (key, bucket) in buckets {
+        let file = File::create(staging.join("buckets").join(format!("{key}.json.gz")))?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::fast());
+        serde_json::to_writer(&mut encoder, bucket)?;
+        encoder.finish()?;
+    }
+    fs::write(staging.join("stamp.json"), serde_json::to_vec(stamp)?)?;
+
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::rename(&staging, dir)?;
+    Ok(())
+}
+
+fn read_gz_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(GzDecoder::new(BufReader::new(
+        file,
+    )))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+
+    fn append(builder: &mut tar::Builder<impl std::io::Write>, path: &str, content: &str) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, content.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_ingest() {
+        let dir = TempDir::new("db-dump");
+        let path = dir.path().join("db-dump.tar.gz");
+
+        let encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        append(
+            &mut builder,
+            "2024-05-01-020000/data/crates.csv",
+            "id,name,repository,description\n1,Demo,https://example.com/demo,A demo\n2,prerel,,\n",
+        );
+        append(
+            &mut builder,
+            "2024-05-01-020000/data/crate_downloads.csv",
+            "crate_id,downloads\n1,1234\n",
+        );
+        append(
+            &mut builder,
+            "2024-05-01-020000/data/versions.csv",
+            "crate_id,num,created_at,license,yanked,rust_version\n\
+             1,1.2.0,2024-03-01 10:00:00,MIT,f,1.70\n\
+             1,1.10.0,2024-04-01 10:00:00,MIT OR Apache-2.0,f,\n\
+             1,1.9.0,2024-02-01 10:00:00,MIT,f,\n\
+             2,0.2.0-beta.1,2024-04-01 10:00:00,,f,\n\
+             2,0.10.0-alpha,2024-05-01 10:00:00,,f,\n\
+             2,0.3.0-rc.1,2024-01-01 10:00:00,,f,\n",
+        );
+        append(&mut builder, "2024-05-01-020000/README.md", "not data");
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let buckets = ingest(&path).unwrap();
+        let demo = &buckets["de"]["demo"];
+        assert_eq!(demo.crate_info.max_version, "1.10.0");
+        assert_eq!(demo.crate_info.downloads, Some(1234));
+        assert_eq!(demo.crate_info.description.as_deref(), Some("A demo"));
+        assert_eq!(demo.versions.len(), 3);
+        let release = demo.versions.iter().find(|v| v.num == "1.2.0").unwrap();
+        assert_eq!(release.rust_version.as_deref(), Some("1.70"));
+        assert_eq!(release.license.as_deref(), Some("MIT"));
+
+        // Only pre-releases: the newest by version, not the last row
+        let prerel = &buckets["pr"]["prerel"];
+        assert_eq!(prerel.crate_info.max_version, "0.10.0-alpha");
+        assert_eq!(prerel.crate_info.repository, None);
+    }
+}
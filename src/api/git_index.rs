@@ -0,0 +1,103 @@
+use anyhow::{Context, Result};
+use crates_index::Crate;
+use log::debug;
+use std::path::Path;
+
+use crate::types::LookupError;
+
+/// A git-protocol registry index as Cargo keeps it: a checkout without a working
+/// tree whose entries live in (packed) git objects. Objects are read in-process
+/// through `crates_index::GitIndex`, at the most recently fetched commit.
+pub struct GitIndex {
+    // The open repository is large; keep `LocalIndex` variants of similar size
+    index: Box<crates_index::GitIndex>,
+}
+
+impl GitIndex {
+    /// Open the index at `path` read-only; nothing is cloned or fetched.
+    pub fn open(path: &Path) -> Result<Self> {
+        let index = crates_index::GitIndex::try_with_path(path, crates_index::git::URL)
+            .with_context(|| format!("Failed to open git index {}", path.display()))?
+            .with_context(|| {
+                format!(
+                    "{} is not a git index of {}",
+                    path.display(),
+                    crates_index::git::URL
+                )
+            })?;
+        debug!(
+            "Using git index {} at {}",
+            path.display(),
+            index.commit_hex()
+        );
+        Ok(GitIndex {
+            index: Box::new(index),
+        })
+    }
+
+    /// The entry of `name`; `NotInIndex` when the index has no such file.
+    pub fn read_crate(&self, name: &str) -> Result<Crate, LookupError> {
+        self.index.crate_(name).ok_or(LookupError::NotInIndex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+    use std::process::Command;
+
+    #[test]
+    fn test_read_entry_from_bare_checkout() {
+        let root = TempDir::new("git-index");
+        let work = root.path().join("work");
+        std::fs::create_dir_all(work.join("3/d")).unwrap();
+        let cksum = "0".repeat(64);
+        std::fs::write(
+            work.join("3/d/dem"),
+            format!("{{\"name\":\"dem\",\"vers\":\"0.1.0\",\"deps\":[],\"cksum\":\"{cksum}\",\"features\":{{}},\"yanked\":false}}\n"),
+        )
+        .unwrap();
+        // Only building the fixture needs the git CLI; reading it does not
+        let run = |dir: &Path, args: &[&str]| {
+            let output = Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=t",
+                    "-c",
+                    "user.email=t@t",
+                    "-c",
+                    "init.defaultBranch=master",
+                ])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .expect("the git CLI builds this test's fixture");
+            assert!(output.status.success(), "git {args:?}");
+        };
+        run(&work, &["init", "-q"]);
+        run(&work, &["add", "."]);
+        run(&work, &["commit", "-qm", "index"]);
+        // Cargo keeps the index as a bare `<dir>/.git`, fetched into origin/HEAD
+        let registry = root.path().join("registry");
+        std::fs::create_dir_all(&registry).unwrap();
+        run(&registry, &["init", "-q", "--bare", ".git"]);
+        run(
+            &registry.join(".git"),
+            &[
+                "fetch",
+                "-q",
+                "../../work",
+                "+HEAD:refs/remotes/origin/HEAD",
+            ],
+        );
+
+        let index = GitIndex::open(&registry).unwrap();
+        let krate = index.read_crate("dem").unwrap();
+        assert_eq!(krate.highest_version().version(), "0.1.0");
+        assert_eq!(
+            index.read_crate("nop").unwrap_err(),
+            LookupError::NotInIndex
+        );
+    }
+}
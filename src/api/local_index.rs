@@ -1,63 +1,397 @@
 use anyhow::Result;
-use crates_index::{HashKind, SparseIndex};
+use crates_index::{Crate, SparseIndex};
 use log::{debug, warn};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::api::git_index::GitIndex;
+use crate::types::{LookupError, PublishedVersion, VersionLookup};
 
 const CRATES_IO_URL: &str = "sparse+https://index.crates.io/";
 
-/// Fetch latest versions from the local crates.io sparse index (~/.cargo/registry/index/).
-/// Returns a map of crate name → latest version string.
-/// Falls back to None for crates not found in the index.
-pub fn fetch_versions_from_local_index(
-    crate_names: &[String],
-) -> Result<HashMap<String, Option<String>>> {
-    // Try Stable hash first (Cargo 1.85+), then Legacy hash (older Cargo)
-    let index = open_sparse_index()?;
+/// Where index entries are read from.
+enum LocalIndex {
+    /// A registry index directory Cargo maintains, e.g. under `$CARGO_HOME/registry/index`.
+    Cargo(CargoIndex),
+    /// A directory laid out like a sparse index (`3/s/syn`, `se/rd/serde`, ...),
+    /// holding one JSON-lines index file per crate.
+    Directory(PathBuf),
+}
+
+/// A registry index directory Cargo maintains. Sparse and git indexes both keep
+/// fetched entries in a `.cache/` tree with the same format, which `SparseIndex`
+/// knows how to read; git indexes hold every other entry in their git objects.
+struct CargoIndex {
+    cache: SparseIndex,
+    git: Option<GitIndex>,
+}
+
+impl CargoIndex {
+    fn open(path: PathBuf) -> Self {
+        let git = is_git_index(&path)
+            .then(|| GitIndex::open(&path))
+            .and_then(|git| {
+                git.inspect_err(|e| warn!("{e:#}; reading only its .cache"))
+                    .ok()
+            });
+        debug!(
+            "Using {} index at {}",
+            if git.is_some() { "git" } else { "sparse" },
+            path.display()
+        );
+        CargoIndex {
+            cache: SparseIndex::at_path(path, CRATES_IO_URL.to_string()),
+            git,
+        }
+    }
+
+    fn read_crate(&self, name: &str) -> Result<Crate, LookupError> {
+        let e = match self.cache.crate_from_cache(name) {
+            Ok(krate) => return Ok(krate),
+            Err(e) => cache_error(e),
+        };
+        match &self.git {
+            Some(git) => git.read_crate(name),
+            None => Err(e),
+        }
+    }
+}
+
+impl LocalIndex {
+    fn open(index_path: Option<&Path>) -> Result<Self> {
+        match index_path {
+            Some(path) => {
+                if !path.is_dir() {
+                    anyhow::bail!("Index path is not a directory: {}", path.display());
+                }
+                if is_cargo_index(path) {
+                    return Ok(LocalIndex::Cargo(CargoIndex::open(path.to_path_buf())));
+                }
+                debug!("Using index directory {}", path.display());
+                Ok(LocalIndex::Directory(path.to_path_buf()))
+            }
+            None => open_crates_io_index(),
+        }
+    }
+
+    fn read_crate(&self, name: &str) -> Result<Crate, LookupError> {
+        match self {
+            LocalIndex::Cargo(index) => index.read_crate(name),
+            LocalIndex::Directory(root) => read_index_file(root, name),
+        }
+    }
+}
+
+fn read_index_file(root: &Path, name: &str) -> Result<Crate, LookupError> {
+    let relative = crate_relative_path(name)
+        .ok_or_else(|| LookupError::Index(format!("invalid crate name '{name}'")))?;
+    let path = root.join(relative);
+    Crate::new(&path).map_err(|e| io_error(&e, &path))
+}
+
+fn cache_error(e: crates_index::Error) -> LookupError {
+    match e {
+        crates_index::Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            LookupError::NotInIndex
+        }
+        e => LookupError::Index(e.to_string()),
+    }
+}
 
-    debug!("Using local crates.io sparse index for version lookup");
+fn io_error(e: &std::io::Error, path: &Path) -> LookupError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        LookupError::NotInIndex
+    } else {
+        LookupError::Index(format!("{}: {e}", path.display()))
+    }
+}
+
+/// Full index entries, read on demand: unlike [`fetch_version_lists_from_local_index`]
+/// this keeps each release's features and dependency list. Crates are cached, as
+/// walking a dependency tree reads the same ones over and over.
+pub struct IndexReader {
+    index: LocalIndex,
+    crates: RefCell<HashMap<String, Result<Rc<Crate>, LookupError>>>,
+}
+
+impl IndexReader {
+    /// Open `index_path`, or the local crates.io index like [`fetch_version_lists_from_local_index`].
+    pub fn open(index_path: Option<&Path>) -> Result<Self> {
+        Ok(IndexReader {
+            index: LocalIndex::open(index_path)?,
+            crates: RefCell::new(HashMap::new()),
+        })
+    }
 
-    let mut results = HashMap::new();
+    pub fn read_crate(&self, name: &str) -> Result<Rc<Crate>, LookupError> {
+        if let Some(cached) = self.crates.borrow().get(name) {
+            return cached.clone();
+        }
+        let krate = self.index.read_crate(name).map(Rc::new);
+        if let Err(e) = &krate {
+            debug!("Local index lookup for '{name}' failed: {e}");
+        }
+        self.crates
+            .borrow_mut()
+            .insert(name.to_string(), krate.clone());
+        krate
+    }
+}
+
+/// Read every published version of the given crates from a local index.
+///
+/// Reads `index_path` when given, otherwise the most recently updated crates.io
+/// index (sparse or git) under ~/.cargo/registry/index/.
+/// Crates the index cannot answer are recorded as failures with the reason.
+pub fn fetch_version_lists_from_local_index(
+    crate_names: &[String],
+    index_path: Option<&Path>,
+) -> Result<VersionLookup> {
+    let index = LocalIndex::open(index_path)?;
+
+    let mut results = VersionLookup::default();
     for name in crate_names {
-        if let Ok(krate) = index.crate_from_cache(name) {
-            if let Some(version) = krate.highest_normal_version() {
-                let version_str = version.version();
-                debug!("Local index: {name} -> {version_str}");
-                results.insert(name.clone(), Some(version_str.to_string()));
-            } else {
-                debug!("Local index: {name} found but no normal version");
-                results.insert(name.clone(), None);
+        match index.read_crate(name) {
+            Ok(krate) => {
+                let versions = krate
+                    .versions()
+                    .iter()
+                    .map(|v| PublishedVersion {
+                        num: v.version().to_string(),
+                        yanked: v.is_yanked(),
+                        rust_version: v.rust_version().map(str::to_string),
+                        features: Some(offered_features(v)),
+                        default_features: Some(
+                            v.features().get("default").cloned().unwrap_or_default(),
+                        ),
+                        ..PublishedVersion::default()
+                    })
+                    .collect();
+                results.versions.insert(name.clone(), versions);
+            }
+            Err(e) => {
+                debug!("Local index lookup for '{name}' failed: {e}");
+                results.failures.insert(name.clone(), e);
             }
-        } else {
-            warn!("Crate '{name}' not found in local index");
-            results.insert(name.clone(), None);
         }
     }
 
     Ok(results)
 }
 
-fn open_sparse_index() -> Result<SparseIndex> {
-    let cargo_home = std::env::var("CARGO_HOME").map_or_else(
-        |_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-            std::path::PathBuf::from(home).join(".cargo")
-        },
-        std::path::PathBuf::from,
+/// Names a release accepts in `features = [...]`: its declared features plus the
+/// implicit feature of every optional dependency no `dep:` entry refers to.
+fn offered_features(version: &crates_index::Version) -> Vec<String> {
+    let mut features: Vec<String> = version.features().keys().cloned().collect();
+    let explicit_deps: Vec<&str> = version
+        .features()
+        .values()
+        .flatten()
+        .filter_map(|entry| entry.strip_prefix("dep:"))
+        .collect();
+    features.extend(
+        version
+            .dependencies()
+            .iter()
+            .filter(|dep| dep.is_optional() && !explicit_deps.contains(&dep.name()))
+            .map(|dep| dep.name().to_string()),
     );
+    features.sort();
+    features.dedup();
+    features
+}
+
+/// The crates.io index under `$CARGO_HOME/registry/index`: of its sparse
+/// (`index.crates.io-*`) and git (`github.com-*`) checkouts, the most recently
+/// updated one. Other registries are never read, so a crate they publish under
+/// a crates.io name cannot pass for the crates.io release.
+fn open_crates_io_index() -> Result<LocalIndex> {
+    let cargo_home = crate::utils::cargo_home();
 
-    // Try Stable hash first (Cargo 1.85+), then Legacy hash
-    for hash_kind in [HashKind::Stable, HashKind::Legacy] {
-        if let Ok(index) =
-            SparseIndex::with_path_and_hash_kind(&cargo_home, CRATES_IO_URL, &hash_kind)
-        {
-            // Verify the index actually works by trying to read a known crate
-            if index.crate_from_cache("serde").is_ok() {
-                debug!("Opened sparse index with {hash_kind:?} hash");
-                return Ok(index);
+    let newest = std::fs::read_dir(cargo_home.join("registry").join("index"))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| crate::utils::is_crates_io_registry(path) && is_cargo_index(path))
+        .max_by_key(|path| last_updated(path));
+
+    if let Some(path) = newest {
+        return Ok(LocalIndex::Cargo(CargoIndex::open(path)));
+    }
+
+    // Fallback: let crates-index figure it out
+    Ok(LocalIndex::Cargo(CargoIndex {
+        cache: SparseIndex::new_cargo_default()?,
+        git: None,
+    }))
+}
+
+/// A directory Cargo maintains as a registry index: it has fetched entries
+/// cached, or is a git checkout.
+fn is_cargo_index(path: &Path) -> bool {
+    path.join(".cache").is_dir() || is_git_index(path)
+}
+
+fn is_git_index(path: &Path) -> bool {
+    path.join(".git").exists() || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+}
+
+/// Most recent modification time among the files Cargo touches when it updates an index.
+fn last_updated(path: &Path) -> Option<SystemTime> {
+    [
+        path.to_path_buf(),
+        path.join(".cache"),
+        path.join(".last-updated"),
+        path.join(".git").join("FETCH_HEAD"),
+    ]
+    .iter()
+    .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+    .max()
+}
+
+/// Relative path of a crate's entry in an index directory, e.g. `3/s/syn` or `se/rd/serde`.
+fn crate_relative_path(name: &str) -> Option<PathBuf> {
+    let name = name.to_ascii_lowercase();
+    let path = match name.len() {
+        0 => return None,
+        1 => PathBuf::from("1"),
+        2 => PathBuf::from("2"),
+        3 => PathBuf::from("3").join(&name[..1]),
+        _ => PathBuf::from(name.get(..2)?).join(name.get(2..4)?),
+    };
+    Some(path.join(name))
+}
+
+/// A throwaway index directory for tests, removed on drop.
+#[cfg(test)]
+pub mod fixture {
+    use super::{IndexReader, crate_relative_path};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// An empty directory for one test, removed on drop, also when the test panics.
+    /// The process id and a counter keep it apart from every other test's.
+    pub struct TempDir {
+        path: PathBuf,
+    }
+
+    impl TempDir {
+        pub fn new(label: &str) -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "cargo-stale-{label}-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            TempDir { path }
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    pub struct FixtureIndex {
+        root: TempDir,
+    }
+
+    impl FixtureIndex {
+        /// An empty index directory; `label` names it after the test.
+        pub fn new(label: &str) -> Self {
+            FixtureIndex {
+                root: TempDir::new(&format!("fixture-{label}")),
             }
         }
+
+        /// Append a release of `name` with normal dependencies given as (name, requirement).
+        pub fn publish(
+            &self,
+            name: &str,
+            version: &str,
+            deps: &[(&str, &str)],
+            links: Option<&str>,
+        ) -> &Self {
+            let deps: Vec<serde_json::Value> = deps
+                .iter()
+                .map(|(dep, req)| {
+                    serde_json::json!({
+                        "name": dep, "req": req, "features": [], "optional": false,
+                        "default_features": true, "target": null, "kind": "normal",
+                    })
+                })
+                .collect();
+            let entry = serde_json::json!({
+                "name": name, "vers": version, "deps": deps, "cksum": "0".repeat(64),
+                "features": {}, "yanked": false, "links": links,
+            });
+            let path = self.root.path().join(crate_relative_path(name).unwrap());
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            std::fs::write(&path, format!("{content}{entry}\n")).unwrap();
+            self
+        }
+
+        pub fn path(&self) -> &Path {
+            self.root.path()
+        }
+
+        pub fn reader(&self) -> IndexReader {
+            IndexReader::open(Some(self.root.path())).unwrap()
+        }
     }
+}
 
-    // Fallback: let crates-index figure it out
-    SparseIndex::new_cargo_default().map_err(Into::into)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_relative_path() {
+        assert_eq!(crate_relative_path("a"), Some(PathBuf::from("1/a")));
+        assert_eq!(crate_relative_path("cc"), Some(PathBuf::from("2/cc")));
+        assert_eq!(crate_relative_path("syn"), Some(PathBuf::from("3/s/syn")));
+        assert_eq!(
+            crate_relative_path("serde"),
+            Some(PathBuf::from("se/rd/serde"))
+        );
+        assert_eq!(
+            crate_relative_path("Inflector"),
+            Some(PathBuf::from("in/fl/inflector"))
+        );
+        assert_eq!(crate_relative_path(""), None);
+    }
+
+    #[test]
+    fn test_missing_crates_are_not_in_index() {
+        let dir = fixture::TempDir::new("index");
+        let root = dir.path();
+        let entry = format!(
+            "{{\"name\":\"dem\",\"vers\":\"0.1.0\",\"deps\":[],\"cksum\":\"{}\",\"features\":{{}},\"yanked\":false}}\n",
+            "0".repeat(64)
+        );
+        std::fs::create_dir_all(root.join("3/d")).unwrap();
+        std::fs::write(root.join("3/d/dem"), entry).unwrap();
+        let names = ["dem".to_string(), "missing".to_string()];
+
+        // An index directory, then a Cargo index whose `.cache` has no entry
+        let lookup = fetch_version_lists_from_local_index(&names, Some(root)).unwrap();
+        assert_eq!(lookup.versions["dem"][0].num, "0.1.0");
+        assert_eq!(lookup.failures["missing"], LookupError::NotInIndex);
+
+        std::fs::create_dir_all(root.join(".cache")).unwrap();
+        let lookup = fetch_version_lists_from_local_index(&names, Some(root)).unwrap();
+        assert_eq!(lookup.failures["missing"], LookupError::NotInIndex);
+    }
 }
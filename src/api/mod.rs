@@ -1,2 +1,11 @@
+pub mod advisory_db;
+pub mod crate_source;
 pub mod crates_io;
+pub mod db_dump;
+pub mod git_index;
 pub mod local_index;
+pub mod publishers;
+pub mod release_graph;
+pub mod semver_checks;
+pub mod snapshot;
+pub mod vendor;
@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// The owners of each crate as of the last `--publishers` run, kept in
+/// cargo-stale's cache directory so the next run can tell what changed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OwnerHistory {
+    crates: BTreeMap<String, Vec<String>>,
+}
+
+impl OwnerHistory {
+    fn path() -> PathBuf {
+        crate::utils::cache_dir().join("owners.json")
+    }
+
+    /// The recorded owners; empty on the first run or when the file is unreadable.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(bytes) = fs::read(&path) else {
+            return OwnerHistory::default();
+        };
+        serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable owner history {}: {e}", path.display());
+            OwnerHistory::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Record the current `owners` of `name` and return the owners added and
+    /// removed since the last run; nothing the first time a crate is seen.
+    pub fn update(&mut self, name: &str, owners: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let changes = self
+            .crates
+            .get(name)
+            .map(|previous| {
+                let added = owners.iter().filter(|o| !previous.contains(o)).cloned();
+                let removed = previous.iter().filter(|o| !owners.contains(o)).cloned();
+                (added.collect(), removed.collect())
+            })
+            .unwrap_or_default();
+        self.crates.insert(name.to_string(), owners);
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_changes_since_last_run() {
+        let owners = |logins: &[&str]| logins.iter().map(ToString::to_string).collect();
+        let mut history = OwnerHistory::default();
+
+        let (added, removed) = history.update("demo", owners(&["alice", "github:org:core"]));
+        assert!(added.is_empty() && removed.is_empty());
+
+        let (added, removed) = history.update("demo", owners(&["github:org:core", "mallory"]));
+        assert_eq!(added, ["mallory"]);
+        assert_eq!(removed, ["alice"]);
+
+        let (added, removed) = history.update("demo", owners(&["github:org:core", "mallory"]));
+        assert!(added.is_empty() && removed.is_empty());
+    }
+}
@@ -0,0 +1,409 @@
+use crates_index::{DependencyKind, Version as Release};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::api::local_index::IndexReader;
+use crate::types::{DependencyChange, DependencyDiff, DependencyType, ReleaseDependency};
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// The release `spec` names: an exact published version, or else the newest
+/// non-yanked release matching it as a requirement (`0.11` → `0.11.27`).
+pub fn find_release<'a>(releases: &'a [Release], spec: &str) -> Option<&'a Release> {
+    if let Some(exact) = releases.iter().find(|r| r.version() == spec) {
+        return Some(exact);
+    }
+    let requirement = VersionReq::parse(spec)?;
+    newest_where(releases, |version| requirement.matches(version))
+}
+
+/// The newest non-yanked release, skipping pre-releases unless `allow_prerelease`.
+pub fn latest_release(releases: &[Release], allow_prerelease: bool) -> Option<&Release> {
+    newest_where(releases, |version| {
+        allow_prerelease || version.pre_release.is_none()
+    })
+}
+
+/// The `from` and `to` releases of `name` a command compares. `from` defaults to
+/// `current()`, `to` to the latest release.
+pub fn release_range<'a>(
+    name: &str,
+    releases: &'a [Release],
+    (from, to): (Option<&str>, Option<&str>),
+    allow_prerelease: bool,
+    current: impl FnOnce() -> anyhow::Result<&'a Release>,
+) -> anyhow::Result<(&'a Release, &'a Release)> {
+    let named = |spec: &str| {
+        find_release(releases, spec)
+            .ok_or_else(|| anyhow::anyhow!("No release of {name} matches '{spec}'"))
+    };
+    let from = match from {
+        Some(spec) => named(spec)?,
+        None => current()?,
+    };
+    let to = match to {
+        Some(spec) => named(spec)?,
+        None => latest_release(releases, allow_prerelease)
+            .ok_or_else(|| anyhow::anyhow!("{name} has no release to compare to"))?,
+    };
+    Ok((from, to))
+}
+
+fn newest_where(releases: &[Release], keep: impl Fn(&Version) -> bool) -> Option<&Release> {
+    releases
+        .iter()
+        .filter(|r| !r.is_yanked())
+        .filter_map(|r| Version::parse(r.version()).map(|parsed| (parsed, r)))
+        .filter(|(parsed, _)| keep(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, release)| release)
+}
+
+/// Compare the dependency lists and the dependency trees of two releases of `name`.
+pub fn diff(reader: &IndexReader, name: &str, from: &Release, to: &Release) -> DependencyDiff {
+    let old_tree = dependency_tree(reader, from);
+    let new_tree = dependency_tree(reader, to);
+    DependencyDiff {
+        name: name.to_string(),
+        from: from.version().to_string(),
+        to: to.version().to_string(),
+        changes: dependency_changes(&release_dependencies(from), &release_dependencies(to)),
+        added_crates: new_tree.difference(&old_tree).cloned().collect(),
+        removed_crates: old_tree.difference(&new_tree).cloned().collect(),
+    }
+}
+
+/// How many crates upgrading `name` from `from` to `to` adds to and removes from
+/// the dependency tree. `None` when the index does not hold both releases.
+pub fn tree_change(
+    reader: &IndexReader,
+    name: &str,
+    from: &str,
+    to: &str,
+) -> Option<(usize, usize)> {
+    let krate = reader.read_crate(name).ok()?;
+    let from = find_release(krate.versions(), from)?;
+    let to = find_release(krate.versions(), to)?;
+    let old_tree = dependency_tree(reader, from);
+    let new_tree = dependency_tree(reader, to);
+    Some((
+        new_tree.difference(&old_tree).count(),
+        old_tree.difference(&new_tree).count(),
+    ))
+}
+
+fn release_dependencies(release: &Release) -> Vec<ReleaseDependency> {
+    let mut dependencies: Vec<ReleaseDependency> = release
+        .dependencies()
+        .iter()
+        .map(|dep| ReleaseDependency {
+            name: dep.crate_name().to_string(),
+            requirement: dep.requirement().to_string(),
+            kind: match dep.kind() {
+                DependencyKind::Normal => DependencyType::Normal,
+                DependencyKind::Dev => DependencyType::Dev,
+                DependencyKind::Build => DependencyType::Build,
+            },
+            optional: dep.is_optional(),
+        })
+        .collect();
+    // Target-specific entries repeat a crate; one per crate and kind is enough here
+    dependencies.sort_by(|a, b| (&a.name, a.kind).cmp(&(&b.name, b.kind)));
+    dependencies.dedup_by(|a, b| a.name == b.name && a.kind == b.kind);
+    dependencies
+}
+
+fn dependency_changes(
+    old: &[ReleaseDependency],
+    new: &[ReleaseDependency],
+) -> Vec<DependencyChange> {
+    let find = |list: &[ReleaseDependency], dep: &ReleaseDependency| {
+        list.iter()
+            .find(|other| other.name == dep.name && other.kind == dep.kind)
+            .cloned()
+    };
+
+    let mut changes = Vec::new();
+    for dep in new {
+        let Some(previous) = find(old, dep) else {
+            changes.push(DependencyChange::Added(dep.clone()));
+            continue;
+        };
+        if previous.requirement != dep.requirement {
+            changes.push(DependencyChange::Requirement {
+                dependency: dep.clone(),
+                from: previous.requirement.clone(),
+            });
+        }
+        match (previous.optional, dep.optional) {
+            (false, true) => changes.push(DependencyChange::BecameOptional(dep.clone())),
+            (true, false) => changes.push(DependencyChange::BecameRequired(dep.clone())),
+            _ => {}
+        }
+    }
+    changes.extend(
+        old.iter()
+            .filter(|dep| find(new, dep).is_none())
+            .map(|dep| DependencyChange::Removed(dep.clone())),
+    );
+    changes
+}
+
+/// A crate to activate with some of its features, as asked for by a dependent.
+struct Activation {
+    name: String,
+    requirement: String,
+    features: Vec<String>,
+    default_features: bool,
+}
+
+/// A crate in the tree: the release picked for it and the features enabled so far.
+struct Activated {
+    release: Release,
+    features: HashSet<String>,
+}
+
+/// Names of every crate a release pulls in; see [`resolve_tree`].
+fn dependency_tree(reader: &IndexReader, root: &Release) -> BTreeSet<String> {
+    resolve_tree(reader, root).into_keys().collect()
+}
+
+/// Every crate a release pulls in when built with its default features, and the
+/// release picked for it: a rough resolution that takes the newest release matching
+/// the first requirement seen for each crate, unifies features per crate and
+/// includes all targets. Dev-dependencies are not part of the tree.
+pub fn resolve_tree(reader: &IndexReader, root: &Release) -> HashMap<String, Release> {
+    let mut tree: HashMap<String, Activated> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    let mut root = Activated {
+        release: root.clone(),
+        features: HashSet::new(),
+    };
+    activate_required(&root.release, &mut queue);
+    enable_feature(&mut root, "default", &mut queue);
+
+    while let Some(activation) = queue.pop_front() {
+        let crate_tree = match tree.entry(activation.name.clone()) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let Some(release) = reader.read_crate(&activation.name).ok().and_then(|krate| {
+                    let requirement = VersionReq::parse(&activation.requirement)?;
+                    newest_where(krate.versions(), |v| requirement.matches(v)).cloned()
+                }) else {
+                    continue;
+                };
+                activate_required(&release, &mut queue);
+                entry.insert(Activated {
+                    release,
+                    features: HashSet::new(),
+                })
+            }
+        };
+        if activation.default_features {
+            enable_feature(crate_tree, "default", &mut queue);
+        }
+        for feature in &activation.features {
+            enable_feature(crate_tree, feature, &mut queue);
+        }
+    }
+
+    tree.remove(root.release.name());
+    tree.into_iter()
+        .map(|(name, activated)| (name, activated.release))
+        .collect()
+}
+
+/// Queue the non-optional dependencies every build of `release` needs.
+fn activate_required(release: &Release, queue: &mut VecDeque<Activation>) {
+    for dep in release.dependencies() {
+        if !dep.is_optional() && dep.kind() != DependencyKind::Dev {
+            queue.push_back(Activation {
+                name: dep.crate_name().to_string(),
+                requirement: dep.requirement().to_string(),
+                features: dep.features().to_vec(),
+                default_features: dep.has_default_features(),
+            });
+        }
+    }
+}
+
+/// Enable `feature` on a crate, queueing the optional dependencies it turns on.
+fn enable_feature(crate_tree: &mut Activated, feature: &str, queue: &mut VecDeque<Activation>) {
+    if !crate_tree.features.insert(feature.to_string()) {
+        return;
+    }
+    let Some(entries) = crate_tree.release.features().get(feature).cloned() else {
+        // An optional dependency's implicit feature
+        enable_dependency(&crate_tree.release, feature, None, queue);
+        return;
+    };
+    for entry in entries {
+        if let Some(dep) = entry.strip_prefix("dep:") {
+            enable_dependency(&crate_tree.release, dep, None, queue);
+        } else if let Some((dep, dep_feature)) = entry.split_once('/') {
+            // `dep?/feature` only applies when something else enables `dep`
+            if !dep.ends_with('?') {
+                enable_dependency(&crate_tree.release, dep, Some(dep_feature), queue);
+            }
+        } else {
+            enable_feature(crate_tree, &entry, queue);
+        }
+    }
+}
+
+/// Queue the dependency `release` knows as `name` (renames included), with an extra feature.
+fn enable_dependency(
+    release: &Release,
+    name: &str,
+    feature: Option<&str>,
+    queue: &mut VecDeque<Activation>,
+) {
+    for dep in release.dependencies() {
+        if dep.name() == name && dep.kind() != DependencyKind::Dev {
+            queue.push_back(Activation {
+                name: dep.crate_name().to_string(),
+                requirement: dep.requirement().to_string(),
+                features: dep
+                    .features()
+                    .iter()
+                    .cloned()
+                    .chain(feature.map(str::to_string))
+                    .collect(),
+                default_features: dep.has_default_features(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::FixtureIndex;
+
+    fn dep(name: &str, requirement: &str, optional: bool) -> ReleaseDependency {
+        ReleaseDependency {
+            name: name.to_string(),
+            requirement: requirement.to_string(),
+            kind: DependencyType::Normal,
+            optional,
+        }
+    }
+
+    #[test]
+    fn test_release_range() {
+        let index = FixtureIndex::new("release-range");
+        for version in ["0.9.0", "1.0.0", "1.2.0", "2.0.0-rc.1"] {
+            index.publish("demo", version, &[], None);
+        }
+        let krate = index.reader().read_crate("demo").unwrap();
+        let releases = krate.versions();
+        let range = |specs, allow_prerelease| {
+            release_range("demo", releases, specs, allow_prerelease, || {
+                Ok(&releases[0])
+            })
+            .map(|(from, to)| (from.version(), to.version()))
+        };
+
+        assert_eq!(range((None, None), false).unwrap(), ("0.9.0", "1.2.0"));
+        assert_eq!(range((None, None), true).unwrap(), ("0.9.0", "2.0.0-rc.1"));
+        assert_eq!(
+            range((Some("1"), Some("1.0.0")), false).unwrap(),
+            ("1.2.0", "1.0.0")
+        );
+        assert!(range((Some("3"), None), false).is_err());
+    }
+
+    #[test]
+    fn test_tree_change() {
+        let index = FixtureIndex::new("tree-change");
+        index
+            .publish(
+                "demo",
+                "1.0.0",
+                &[("shared", "^1"), ("old-only", "^1")],
+                None,
+            )
+            .publish(
+                "demo",
+                "2.0.0",
+                &[("shared", "^1"), ("new-only", "^1")],
+                None,
+            )
+            .publish("shared", "1.0.0", &[("shared-leaf", "^1")], None)
+            .publish("shared-leaf", "1.0.0", &[], None)
+            .publish("old-only", "1.0.0", &[("old-leaf", "^1")], None)
+            .publish("old-leaf", "1.0.0", &[], None)
+            .publish(
+                "new-only",
+                "1.0.0",
+                &[("new-leaf", "^1"), ("shared-leaf", "^1")],
+                None,
+            )
+            .publish("new-leaf", "1.0.0", &[("new-deep", "^1")], None)
+            .publish("new-deep", "1.0.0", &[], None);
+        let reader = index.reader();
+
+        let krate = reader.read_crate("demo").unwrap();
+        let mut tree: Vec<String> = resolve_tree(&reader, &krate.versions()[0])
+            .into_keys()
+            .collect();
+        tree.sort();
+        assert_eq!(tree, ["old-leaf", "old-only", "shared", "shared-leaf"]);
+
+        // new-only, new-leaf and new-deep come in, old-only and old-leaf go, while
+        // shared-leaf stays although it moves under new-only: a net change of +1
+        assert_eq!(tree_change(&reader, "demo", "1.0.0", "2.0.0"), Some((3, 2)));
+        assert_eq!(tree_change(&reader, "demo", "1.0.0", "3.0.0"), None);
+    }
+
+    #[test]
+    fn test_release_dependencies_dedup() {
+        let entry = |name: &str, kind: &str, target: &str| {
+            format!(
+                "{{\"name\":\"{name}\",\"req\":\"^1\",\"features\":[],\"optional\":false,\"default_features\":true,\"target\":{target},\"kind\":\"{kind}\"}}"
+            )
+        };
+        let deps = [
+            entry("libc", "normal", "\"cfg(unix)\""),
+            entry("libc", "dev", "null"),
+            entry("libc", "normal", "\"cfg(windows)\""),
+        ];
+        let line = format!(
+            "{{\"name\":\"demo\",\"vers\":\"1.0.0\",\"deps\":[{}],\"cksum\":\"{}\",\"features\":{{}},\"yanked\":false}}",
+            deps.join(","),
+            "0".repeat(64)
+        );
+        let krate = crates_index::Crate::from_slice(line.as_bytes()).unwrap();
+        let kinds: Vec<DependencyType> = release_dependencies(krate.highest_version())
+            .iter()
+            .map(|dep| dep.kind)
+            .collect();
+        assert_eq!(kinds, [DependencyType::Normal, DependencyType::Dev]);
+    }
+
+    #[test]
+    fn test_dependency_changes() {
+        let old = [
+            dep("hyper", "^0.14", false),
+            dep("hyper-tls", "^0.5", true),
+            dep("mime", "^0.3", false),
+        ];
+        let new = [
+            dep("hyper", "^1.1", false),
+            dep("hyper-util", "^0.1", false),
+            dep("mime", "^0.3", true),
+        ];
+        assert_eq!(
+            dependency_changes(&old, &new),
+            vec![
+                DependencyChange::Requirement {
+                    dependency: dep("hyper", "^1.1", false),
+                    from: "^0.14".to_string(),
+                },
+                DependencyChange::Added(dep("hyper-util", "^0.1", false)),
+                DependencyChange::BecameOptional(dep("mime", "^0.3", true)),
+                DependencyChange::Removed(dep("hyper-tls", "^0.5", true)),
+            ]
+        );
+    }
+}
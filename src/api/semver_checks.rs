@@ -0,0 +1,111 @@
+use log::debug;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::api::crate_source::CrateSource;
+
+/// Whether `cargo semver-checks` is installed.
+pub fn is_installed() -> bool {
+    Command::new("cargo")
+        .args(["semver-checks", "--version"])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Run `cargo semver-checks` with release `from` of `name` as the baseline and
+/// `to` as the current version. Both sources must be in Cargo's registry cache;
+/// they are checked from copies under cargo-stale's cache directory, which also
+/// holds the rustdoc builds.
+///
+/// Returns how often each lint failed (empty when the API is compatible), or
+/// why the check could not run.
+pub fn check(name: &str, from: &str, to: &str) -> Result<Vec<(String, usize)>, String> {
+    let cache = crate::utils::cache_dir();
+    let unpack = |version: &str| {
+        CrateSource::find(name, version)
+            .ok_or_else(|| format!("{name} {version} is not in Cargo's registry cache"))?
+            .unpack_into(&cache.join("sources"))
+            .map_err(|e| e.to_string())
+    };
+    let baseline = unpack(from)?;
+    let current = unpack(to)?;
+
+    debug!("cargo semver-checks: {name} {from} → {to}");
+    let output = Command::new("cargo")
+        .args(["semver-checks", "check-release", "--manifest-path"])
+        .arg(current.join("Cargo.toml"))
+        .arg("--baseline-root")
+        .arg(&baseline)
+        .env("CARGO_TARGET_DIR", cache.join("semver-checks-target"))
+        .current_dir(current.parent().unwrap_or(Path::new(".")))
+        .output()
+        .map_err(|e| format!("failed to run cargo semver-checks: {e}"))?;
+
+    let report = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let breaks = parse_failures(&report);
+    if output.status.success() || !breaks.is_empty() {
+        return Ok(breaks);
+    }
+    Err(report
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .unwrap_or("cargo semver-checks failed")
+        .to_string())
+}
+
+/// Failed lints in a `cargo semver-checks` report and how many API items each
+/// broke: every `--- failure <lint>: ... ---` block lists them, one per line,
+/// under `Failed in:`.
+fn parse_failures(report: &str) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut lint: Option<String> = None;
+    let mut in_items = false;
+    for line in report.lines() {
+        let trimmed = line.trim();
+        if let Some((name, _)) = trimmed
+            .strip_prefix("--- failure ")
+            .and_then(|rest| rest.split_once(':'))
+        {
+            let name = name.trim().to_string();
+            // A block without item lines still counts as one break
+            counts.entry(name.clone()).or_insert(1);
+            lint = Some(name);
+            in_items = false;
+        } else if trimmed == "Failed in:" {
+            in_items = lint.is_some();
+            if let Some(name) = &lint {
+                counts.insert(name.clone(), 0);
+            }
+        } else if !(line.starts_with("  ") && line[2..].starts_with(|c: char| !c.is_whitespace())) {
+            // Items are indented by two spaces; the summary and blank lines end the list
+            in_items = false;
+        } else if in_items && let Some(name) = &lint {
+            *counts.entry(name.clone()).or_default() += 1;
+        }
+    }
+    counts.into_iter().filter(|(_, count)| *count > 0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_failures() {
+        let report = "     Checked [   0.010s] 120 checks: 118 pass, 2 fail, 0 warn, 0 skip\n\n--- failure function_missing: pub fn removed or renamed ---\n\nDescription:\nA publicly-visible function cannot be imported by its prior path.\n        ref: https://doc.rust-lang.org/cargo/reference/semver.html\n\nFailed in:\n  function demo::parse, previously in file src/lib.rs:10\n  function demo::load, previously in file src/lib.rs:24\n\n--- failure enum_variant_added: enum variant added on exhaustive enum ---\n\nFailed in:\n  variant Mode:Fast in src/lib.rs:3\n     Summary semver requires new major version: 2 major and 0 minor checks failed\n";
+        assert_eq!(
+            parse_failures(report),
+            [
+                ("enum_variant_added".to_string(), 1),
+                ("function_missing".to_string(), 2),
+            ]
+        );
+        assert!(parse_failures("     Summary no semver update required\n").is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::types::{LookupError, PublishedVersion, VersionLookup};
+
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Resolved version lists for a set of crates, written as gzip-compressed JSON
+/// so they can be committed or cached and consumed without network access.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: u32,
+    /// Creation time in seconds since the Unix epoch
+    pub created_at: u64,
+    pub crates: BTreeMap<String, Vec<PublishedVersion>>,
+}
+
+impl Snapshot {
+    pub fn new(crates: BTreeMap<String, Vec<PublishedVersion>>) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Snapshot {
+            format: SNAPSHOT_FORMAT,
+            created_at,
+            crates,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open snapshot: {}", path.display()))?;
+        let snapshot: Snapshot = serde_json::from_reader(GzDecoder::new(BufReader::new(file)))
+            .with_context(|| format!("Failed to parse snapshot: {}", path.display()))?;
+        if snapshot.format != SNAPSHOT_FORMAT {
+            anyhow::bail!(
+                "Unsupported snapshot format {} (expected {SNAPSHOT_FORMAT}): {}",
+                snapshot.format,
+                path.display()
+            );
+        }
+        Ok(snapshot)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create snapshot: {}", path.display()))?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::best());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// The version lists of `names`. A crate the snapshot lacks is a failed lookup,
+    /// not an unknown crate: the snapshot may predate the dependency.
+    pub fn lookup(mut self, names: &[String]) -> VersionLookup {
+        let mut lookup = VersionLookup::default();
+        for name in names {
+            match self.crates.remove(name) {
+                Some(versions) => {
+                    lookup.versions.insert(name.clone(), versions);
+                }
+                None => {
+                    lookup
+                        .failures
+                        .insert(name.clone(), LookupError::NotInSnapshot);
+                }
+            }
+        }
+        lookup
+    }
+
+    /// Creation date as `YYYY-MM-DD` (UTC)
+    pub fn created_date(&self) -> String {
+        crate::utils::format_unix_date(self.created_at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir = TempDir::new("snapshot");
+        let path = dir.path().join("snapshot.json.gz");
+
+        let release = |num: &str, yanked| PublishedVersion {
+            num: num.to_string(),
+            yanked,
+            features: Some(vec!["std".to_string()]),
+            ..PublishedVersion::default()
+        };
+        let crates = BTreeMap::from([(
+            "demo".to_string(),
+            vec![release("1.0.0", false), release("1.1.0", true)],
+        )]);
+        let snapshot = Snapshot::new(crates);
+        snapshot.write(&path).unwrap();
+
+        let loaded = Snapshot::load(&path).unwrap();
+        assert_eq!(loaded.created_at, snapshot.created_at);
+        let versions = &loaded.crates["demo"];
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[1].num, "1.1.0");
+        assert!(versions[1].yanked);
+        assert_eq!(
+            versions[0].features.as_deref(),
+            Some(&["std".to_string()][..])
+        );
+
+        Snapshot {
+            format: SNAPSHOT_FORMAT + 1,
+            ..loaded
+        }
+        .write(&path)
+        .unwrap();
+        assert!(Snapshot::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_lookup_missing_crate() {
+        let snapshot = Snapshot::new(BTreeMap::from([(
+            "demo".to_string(),
+            vec![PublishedVersion {
+                num: "1.0.0".to_string(),
+                ..PublishedVersion::default()
+            }],
+        )]));
+        let lookup = snapshot.lookup(&["demo".to_string(), "added-later".to_string()]);
+        assert_eq!(lookup.versions["demo"][0].num, "1.0.0");
+        assert_eq!(lookup.failures["added-later"], LookupError::NotInSnapshot);
+    }
+}
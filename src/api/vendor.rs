@@ -0,0 +1,243 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+use crate::types::{DeclaredDependency, LookupError, PublishedVersion, VendorLag, VersionLookup};
+use crate::version::requirement::VersionReq;
+
+/// An offline mirror whose contents define what "latest" means.
+#[derive(Debug, Clone)]
+pub enum VendorSource {
+    /// A `cargo vendor` output directory (`source.<name>.directory`)
+    Directory(PathBuf),
+    /// A `cargo local-registry` directory (`source.<name>.local-registry`)
+    LocalRegistry(PathBuf),
+}
+
+impl VendorSource {
+    pub fn path(&self) -> &Path {
+        match self {
+            VendorSource::Directory(path) | VendorSource::LocalRegistry(path) => path,
+        }
+    }
+}
+
+/// Collect the versions of the given crates available in a vendored mirror.
+/// Crates the mirror does not hold are left out; entries a local registry holds
+/// but cannot read are recorded as failures.
+pub fn fetch_vendored_versions(
+    crate_names: &[String],
+    source: &VendorSource,
+) -> Result<VersionLookup> {
+    match source {
+        VendorSource::Directory(path) => {
+            let mut vendored = read_vendor_directory(path)?;
+            vendored.retain(|name, _| crate_names.contains(name));
+            Ok(vendored.into())
+        }
+        // A local registry carries an index with the same layout as the sparse index
+        VendorSource::LocalRegistry(path) => {
+            let mut lookup = crate::api::local_index::fetch_version_lists_from_local_index(
+                crate_names,
+                Some(&path.join("index")),
+            )?;
+            lookup.failures.retain(|_, e| *e != LookupError::NotInIndex);
+            Ok(lookup)
+        }
+    }
+}
+
+/// Read the package name and version of every crate in a `cargo vendor` directory.
+/// Entries are named `<name>` or `<name>-<version>` and hold a normalized Cargo.toml.
+fn read_vendor_directory(path: &Path) -> Result<HashMap<String, Vec<PublishedVersion>>> {
+    let entries = fs::read_dir(path)
+        .with_context(|| format!("Failed to read vendor directory: {}", path.display()))?;
+
+    let mut vendored: HashMap<String, Vec<PublishedVersion>> = HashMap::new();
+    for entry in entries.filter_map(Result::ok) {
+        let manifest = entry.path().join("Cargo.toml");
+        let Ok(content) = fs::read_to_string(&manifest) else {
+            continue;
+        };
+        let package = toml::from_str::<Value>(&content)
+            .ok()
+            .and_then(|toml| toml.get("package").cloned());
+        let name = package
+            .as_ref()
+            .and_then(|p| p.get("name"))
+            .and_then(Value::as_str);
+        let version = package
+            .as_ref()
+            .and_then(|p| p.get("version"))
+            .and_then(Value::as_str);
+        match (name, version) {
+            (Some(name), Some(version)) => {
+                debug!("Vendored: {name} {version}");
+                vendored
+                    .entry(name.to_string())
+                    .or_default()
+                    .push(PublishedVersion {
+                        num: version.to_string(),
+                        rust_version: package
+                            .as_ref()
+                            .and_then(|p| p.get("rust-version"))
+                            .and_then(Value::as_str)
+                            .map(str::to_string),
+                        ..PublishedVersion::default()
+                    });
+            }
+            _ => warn!(
+                "Skipping vendored entry without package name/version: {}",
+                manifest.display()
+            ),
+        }
+    }
+
+    Ok(vendored)
+}
+
+/// Find dependencies whose requirement no vendored copy satisfies, so a build
+/// against the mirror would fail to resolve them.
+pub fn find_lagging(
+    all_dependencies: &[DeclaredDependency],
+    vendored: &HashMap<String, Vec<PublishedVersion>>,
+) -> Vec<VendorLag> {
+    all_dependencies
+        .iter()
+        .filter_map(|(name, requirement, _, source)| {
+            let req = VersionReq::parse(requirement)?;
+            let versions: Vec<String> = vendored
+                .get(name)
+                .map(|versions| versions.iter().map(|v| v.num.clone()).collect())
+                .unwrap_or_default();
+            if versions.iter().any(|v| req.matches_str(v)) {
+                return None;
+            }
+            Some(VendorLag {
+                name: name.clone(),
+                requirement: requirement.clone(),
+                source: source.clone(),
+                vendored: versions,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+    use crate::types::DependencyType;
+
+    fn declared(name: &str, requirement: &str) -> DeclaredDependency {
+        (
+            name.to_string(),
+            requirement.to_string(),
+            DependencyType::Normal,
+            "root".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_vendor_directory() {
+        let dir = TempDir::new("vendor");
+        let root = dir.path();
+        for (dir, name, version) in [
+            ("log", "log", "0.4.20"),
+            ("syn", "syn", "2.0.50"),
+            ("syn-1.0.109", "syn", "1.0.109"),
+        ] {
+            let crate_dir = root.join(dir);
+            fs::create_dir_all(&crate_dir).unwrap();
+            fs::write(
+                crate_dir.join("Cargo.toml"),
+                format!("[package]\nname = \"{name}\"\nversion = \"{version}\"\n"),
+            )
+            .unwrap();
+            fs::write(
+                crate_dir.join(".cargo-checksum.json"),
+                "{\"files\":{},\"package\":\"00\"}",
+            )
+            .unwrap();
+        }
+        let names = ["syn".to_string(), "serde".to_string()];
+
+        let lookup =
+            fetch_vendored_versions(&names, &VendorSource::Directory(root.to_path_buf())).unwrap();
+        let mut syn: Vec<&str> = lookup.versions["syn"]
+            .iter()
+            .map(|v| v.num.as_str())
+            .collect();
+        syn.sort_unstable();
+        assert_eq!(syn, ["1.0.109", "2.0.50"]);
+        // Neither unrequested nor missing crates are reported
+        assert_eq!(lookup.versions.len(), 1);
+        assert!(lookup.failures.is_empty());
+    }
+
+    #[test]
+    fn test_local_registry() {
+        let dir = TempDir::new("local-registry");
+        let root = dir.path();
+        let index = root.join("index");
+        fs::create_dir_all(index.join("3/l")).unwrap();
+        fs::create_dir_all(index.join("ra/nd")).unwrap();
+        fs::write(
+            index.join("3/l/log"),
+            format!(
+                "{{\"name\":\"log\",\"vers\":\"0.4.20\",\"deps\":[],\"cksum\":\"{}\",\"features\":{{}},\"yanked\":false}}\n",
+                "0".repeat(64)
+            ),
+        )
+        .unwrap();
+        fs::write(index.join("ra/nd/rand"), "not an index entry\n").unwrap();
+        let names = ["log".to_string(), "rand".to_string(), "serde".to_string()];
+
+        let lookup =
+            fetch_vendored_versions(&names, &VendorSource::LocalRegistry(root.to_path_buf()))
+                .unwrap();
+        assert_eq!(lookup.versions["log"][0].num, "0.4.20");
+        // A broken entry is a failure, a crate the mirror lacks is simply absent
+        assert!(matches!(lookup.failures["rand"], LookupError::Index(_)));
+        assert!(!lookup.failures.contains_key("serde"));
+    }
+
+    #[test]
+    fn test_find_lagging() {
+        let vendored = HashMap::from([(
+            "syn".to_string(),
+            vec![PublishedVersion {
+                num: "1.0.109".to_string(),
+                ..PublishedVersion::default()
+            }],
+        )]);
+        let lagging = find_lagging(
+            &[
+                declared("syn", "1.0"),
+                declared("syn", "2"),
+                declared("log", "0.4"),
+            ],
+            &vendored,
+        );
+        let lagging: Vec<(&str, &str, &[String])> = lagging
+            .iter()
+            .map(|lag| {
+                (
+                    lag.name.as_str(),
+                    lag.requirement.as_str(),
+                    lag.vendored.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lagging,
+            [
+                ("syn", "2", &["1.0.109".to_string()][..]),
+                ("log", "0.4", &[][..]),
+            ]
+        );
+    }
+}
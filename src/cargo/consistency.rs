@@ -0,0 +1,146 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::types::{ConflictResolution, ManifestConflict, PublishedVersion, TableRequirement};
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// Packages a manifest declares with different requirements across its tables.
+///
+/// Cargo resolves a single version of a package for all of them, so whichever
+/// entry has the highest minimum decides what every table gets. `published`
+/// refines that to the release it resolves to, or shows that none fits them all.
+pub fn find_conflicts(
+    source: &str,
+    requirements: &[TableRequirement],
+    published: &HashMap<String, Vec<PublishedVersion>>,
+) -> Vec<ManifestConflict> {
+    let mut by_package: BTreeMap<&str, Vec<&TableRequirement>> = BTreeMap::new();
+    for entry in requirements {
+        by_package.entry(&entry.package).or_default().push(entry);
+    }
+
+    let mut conflicts = Vec::new();
+    for (package, entries) in by_package {
+        let parsed: Option<Vec<VersionReq>> = entries
+            .iter()
+            .map(|e| VersionReq::parse(&e.requirement))
+            .collect();
+        let Some(parsed) = parsed else {
+            // Unparsable requirements are the requirement lints' business
+            continue;
+        };
+        // `1`, `1.0` and `^1` differ only in spelling
+        if parsed.iter().all(|req| req.is_equivalent(&parsed[0])) {
+            continue;
+        }
+
+        conflicts.push(ManifestConflict {
+            source: source.to_string(),
+            package: package.to_string(),
+            declarations: entries
+                .iter()
+                .map(|e| (e.table.clone(), e.requirement.clone()))
+                .collect(),
+            resolution: resolve(&entries, &parsed, published.get(package)),
+        });
+    }
+    conflicts
+}
+
+fn resolve(
+    entries: &[&TableRequirement],
+    parsed: &[VersionReq],
+    published: Option<&Vec<PublishedVersion>>,
+) -> ConflictResolution {
+    let highest = entries
+        .iter()
+        .zip(parsed)
+        .filter_map(|(entry, req)| Some((&entry.requirement, req.lower_bound()?)))
+        .max_by(|(_, a), (_, b)| a.cmp(b));
+    let governing = highest
+        .as_ref()
+        .map_or(&entries[0].requirement, |(r, _)| *r)
+        .clone();
+    let satisfies_all = |version: &Version| parsed.iter().all(|req| req.matches(version));
+
+    let fits = match published.filter(|p| !p.is_empty()) {
+        Some(published) => {
+            let newest = published
+                .iter()
+                .filter(|v| !v.yanked)
+                .filter_map(|v| Version::parse(&v.num))
+                .filter(|v| satisfies_all(v))
+                .max();
+            newest.map(|v| Some(v.to_string()))
+        }
+        // Without a version list, the governing minimum must satisfy the rest
+        None => highest
+            .is_some_and(|(_, lowest)| satisfies_all(&lowest))
+            .then_some(None),
+    };
+    match fits {
+        Some(version) => ConflictResolution::Unified {
+            requirement: governing,
+            version,
+        },
+        None => ConflictResolution::Incompatible {
+            suggested: governing,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(table: &str, requirement: &str) -> TableRequirement {
+        TableRequirement {
+            package: "serde".to_string(),
+            table: table.to_string(),
+            requirement: requirement.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let published = HashMap::from([(
+            "serde".to_string(),
+            ["1.0.100", "1.0.150", "1.0.210", "2.0.0"]
+                .iter()
+                .map(|num| PublishedVersion {
+                    num: (*num).to_string(),
+                    ..PublishedVersion::default()
+                })
+                .collect(),
+        )]);
+
+        let compatible = [
+            entry("dependencies", "1.0.100"),
+            entry("target.'cfg(windows)'.dependencies", "1.0.150"),
+        ];
+        let conflicts = find_conflicts("root", &compatible, &published);
+        assert_eq!(
+            conflicts[0].resolution,
+            ConflictResolution::Unified {
+                requirement: "1.0.150".to_string(),
+                version: Some("1.0.210".to_string()),
+            }
+        );
+
+        let incompatible = [entry("dependencies", "1"), entry("dev-dependencies", "2")];
+        let conflicts = find_conflicts("root", &incompatible, &HashMap::new());
+        assert_eq!(
+            conflicts[0].resolution,
+            ConflictResolution::Incompatible {
+                suggested: "2".to_string()
+            }
+        );
+
+        let same = [
+            entry("dependencies", "1"),
+            entry("dev-dependencies", "1.0"),
+            entry("build-dependencies", "^1"),
+        ];
+        assert!(find_conflicts("root", &same, &published).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found while loading a manifest, e.g. a TOML syntax error or a
+/// workspace member that does not exist.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based line and column, when the problem can be pinned to a position
+    pub location: Option<(usize, usize)>,
+}
+
+impl Diagnostic {
+    pub fn error(path: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: path.to_string(),
+            severity: Severity::Error,
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    pub fn warning(path: &str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(path, message)
+        }
+    }
+
+    pub fn at(mut self, location: Option<(usize, usize)>) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// A TOML parse error, positioned at the span the parser reported.
+    pub fn from_toml(path: &str, content: &str, error: &toml::de::Error) -> Self {
+        Diagnostic::error(path, error.message().trim())
+            .at(error.span().map(|span| line_col(content, span.start)))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, col)) => write!(f, "{}:{line}:{col}: ", self.path)?,
+            None => write!(f, "{}: ", self.path)?,
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// 1-based line and column of a byte offset into `content`.
+pub fn line_col(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let col = before
+        .rfind('\n')
+        .map_or(before, |newline| &before[newline + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, col)
+}
+
+/// Position of the first occurrence of `needle` in `content`.
+pub fn find(content: &str, needle: &str) -> Option<(usize, usize)> {
+    content.find(needle).map(|offset| line_col(content, offset))
+}
+
+/// Position of the first line assigning `key`, e.g. `serde = 1`.
+pub fn find_key(content: &str, key: &str) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let assigns = trimmed
+            .strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+        if assigns {
+            return Some(line_col(content, offset + line.len() - trimmed.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let content = "[package]\nname = \"demo\"\n\n[dependencies]\n  serde = 1\n";
+        assert_eq!(line_col(content, 0), (1, 1));
+        assert_eq!(find(content, "\"demo\""), Some((2, 8)));
+        assert_eq!(find_key(content, "serde"), Some((5, 3)));
+        assert_eq!(find_key(content, "ser"), None);
+    }
+
+    #[test]
+    fn test_toml_error_location() {
+        let content = "[package]\nname = \"demo\n";
+        let error = toml::from_str::<toml::Value>(content).unwrap_err();
+        let diagnostic = Diagnostic::from_toml("Cargo.toml", content, &error);
+        assert_eq!(diagnostic.location.map(|(line, _)| line), Some(2));
+        assert!(diagnostic.to_string().starts_with("Cargo.toml:2:"));
+    }
+}
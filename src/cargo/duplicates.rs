@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::api::local_index::IndexReader;
+use crate::api::release_graph::latest_release;
+use crate::cargo::lockfile::{LockGraph, LockedPackage};
+use crate::types::{DuplicateCopy, DuplicateCrate, DuplicateHolder, HolderFix};
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// Registry crates locked in more than one version, each older copy with the
+/// packages holding it and whether upgrading them would drop it. Without an
+/// index the fixes are [`HolderFix::Unknown`].
+///
+/// Removable copies come first, then those with the fewest holders: the
+/// cheapest wins lead the list.
+pub fn find_duplicates(
+    reader: Option<&IndexReader>,
+    packages: &[LockedPackage],
+) -> Vec<DuplicateCrate> {
+    let graph = LockGraph::new(packages);
+    let mut by_name: BTreeMap<&str, Vec<(Version, usize)>> = BTreeMap::new();
+    for (index, package) in packages.iter().enumerate() {
+        if let Some(version) = Version::parse(&package.version)
+            && package.is_from_registry()
+        {
+            by_name
+                .entry(package.name.as_str())
+                .or_default()
+                .push((version, index));
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateCrate> = by_name
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, mut versions)| {
+            versions.sort_by(|(a, _), (b, _)| b.cmp(a));
+            let newest = versions[0].0.clone();
+            let copies = versions[1..]
+                .iter()
+                .map(|&(_, index)| DuplicateCopy {
+                    version: packages[index].version.clone(),
+                    holders: graph
+                        .dependents(index)
+                        .into_iter()
+                        .map(|holder| {
+                            let package = &packages[holder];
+                            DuplicateHolder {
+                                name: package.name.clone(),
+                                version: package.version.clone(),
+                                fix: holder_fix(reader, package, name, &newest),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect();
+            DuplicateCrate {
+                name: name.to_string(),
+                newest: newest.to_string(),
+                copies,
+            }
+        })
+        .collect();
+
+    duplicates.sort_by_key(|duplicate| {
+        let removable = duplicate.copies.iter().all(DuplicateCopy::is_removable);
+        let holders: usize = duplicate.copies.iter().map(|c| c.holders.len()).sum();
+        (!removable, holders)
+    });
+    duplicates
+}
+
+/// Whether `holder`'s latest release stops requiring an old copy of `name`.
+fn holder_fix(
+    reader: Option<&IndexReader>,
+    holder: &LockedPackage,
+    name: &str,
+    newest: &Version,
+) -> HolderFix {
+    if holder.source.is_none() {
+        return HolderFix::Member;
+    }
+    let Some(krate) = reader.and_then(|reader| reader.read_crate(&holder.name).ok()) else {
+        return HolderFix::Unknown;
+    };
+    let Some(latest) = latest_release(krate.versions(), false) else {
+        return HolderFix::Unknown;
+    };
+
+    let stuck_on = latest
+        .dependencies()
+        .iter()
+        .filter(|dep| dep.crate_name() == name && dep.kind() != crates_index::DependencyKind::Dev)
+        .find(|dep| VersionReq::parse(dep.requirement()).is_some_and(|req| !req.matches(newest)));
+    match stuck_on {
+        Some(dep) => HolderFix::Stuck {
+            latest: latest.version().to_string(),
+            requirement: dep.requirement().to_string(),
+        },
+        None => HolderFix::Upgrade(latest.version().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_duplicates() {
+        let registry = Some("registry+https://github.com/rust-lang/crates.io-index".to_string());
+        let package = |name: &str, version: &str, deps: &[&str]| LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: registry.clone(),
+            dependencies: deps.iter().map(|d| (*d).to_string()).collect(),
+        };
+        let packages = [
+            LockedPackage {
+                source: None,
+                ..package("app", "0.1.0", &["derive 1.0.0", "syn 2.0.87"])
+            },
+            package("derive", "1.0.0", &["syn 1.0.109"]),
+            package("syn", "1.0.109", &[]),
+            package("syn", "2.0.87", &[]),
+            package("serde", "1.0.200", &[]),
+        ];
+
+        let duplicates = find_duplicates(None, &packages);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].newest, "2.0.87");
+        assert_eq!(duplicates[0].copies[0].version, "1.0.109");
+        assert_eq!(
+            duplicates[0].copies[0].holders,
+            vec![DuplicateHolder {
+                name: "derive".to_string(),
+                version: "1.0.0".to_string(),
+                fix: HolderFix::Unknown,
+            }]
+        );
+        assert!(!duplicates[0].copies[0].is_removable());
+    }
+
+    #[test]
+    fn test_is_removable() {
+        let holder = |name: &str, fix| DuplicateHolder {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            fix,
+        };
+        let copy = |holders| DuplicateCopy {
+            version: "1.0.109".to_string(),
+            holders,
+        };
+        let upgrade = || holder("derive", HolderFix::Upgrade("1.2.0".to_string()));
+
+        assert!(copy(vec![upgrade()]).is_removable());
+        // The member's requirement has to be raised by hand
+        assert!(!copy(vec![upgrade(), holder("app", HolderFix::Member)]).is_removable());
+        assert!(!copy(vec![holder("app", HolderFix::Member)]).is_removable());
+        assert!(!copy(Vec::new()).is_removable());
+    }
+}
@@ -0,0 +1,403 @@
+use anyhow::{Context, Result};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use toml_edit::{DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::types::{DeclaredDependency, DependencyType, HygieneFinding, MemberRequirement};
+use crate::version::comparison::compatible_range;
+use crate::version::requirement::VersionReq;
+
+/// Keys that make an entry more than a registry requirement; such entries are
+/// reported but never rewritten.
+const UNTOUCHABLE_KEYS: [&str; 5] = ["path", "git", "package", "registry", "default-features"];
+
+/// Compare how members declare their dependencies, from what
+/// `collect_dependencies` gathered: `[workspace.dependencies]` entries are the
+/// `Workspace` ones, everything else is a member's own declaration.
+pub fn check(dependencies: &[DeclaredDependency]) -> Vec<HygieneFinding> {
+    let mut workspace: HashMap<&str, &str> = HashMap::new();
+    let mut direct: BTreeMap<&str, Vec<MemberRequirement>> = BTreeMap::new();
+    for (name, requirement, dep_type, source) in dependencies {
+        if *dep_type == DependencyType::Workspace {
+            workspace.insert(name, requirement);
+        } else {
+            direct
+                .entry(name)
+                .or_default()
+                .push((source.clone(), *dep_type, requirement.clone()));
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (name, declarations) in direct {
+        if let Some(workspace_requirement) = workspace.get(name) {
+            let (usable, newer): (Vec<_>, Vec<_>) = declarations
+                .into_iter()
+                .partition(|(_, _, requirement)| covers(workspace_requirement, requirement));
+            if !newer.is_empty() {
+                let mut requirements =
+                    vec![("workspace".to_string(), workspace_requirement.to_string())];
+                requirements.extend(
+                    newer
+                        .into_iter()
+                        .map(|(member, _, requirement)| (member, requirement)),
+                );
+                findings.push(HygieneFinding::Mismatch {
+                    name: name.to_string(),
+                    requirements,
+                });
+            }
+            findings.extend(
+                usable
+                    .into_iter()
+                    .map(|member| HygieneFinding::UseWorkspace {
+                        name: name.to_string(),
+                        member,
+                        workspace_requirement: workspace_requirement.to_string(),
+                    }),
+            );
+            continue;
+        }
+
+        let mut members: Vec<&str> = declarations
+            .iter()
+            .map(|(member, _, _)| member.as_str())
+            .collect();
+        members.sort_unstable();
+        members.dedup();
+        if members.len() < 2 {
+            continue;
+        }
+
+        let mut requirements: Vec<(String, String)> = declarations
+            .iter()
+            .map(|(member, _, requirement)| (member.clone(), requirement.clone()))
+            .collect();
+        requirements.sort();
+        requirements.dedup();
+        if requirements.iter().any(|(_, r)| *r != requirements[0].1) {
+            findings.push(HygieneFinding::Mismatch {
+                name: name.to_string(),
+                requirements,
+            });
+        }
+
+        // The requirement with the highest minimum, if it can serve every member
+        let highest = declarations
+            .iter()
+            .map(|(_, _, requirement)| requirement)
+            .max_by_key(|requirement| VersionReq::parse(requirement).and_then(|r| r.lower_bound()));
+        if let Some(highest) = highest
+            && declarations
+                .iter()
+                .all(|(_, _, requirement)| covers(highest, requirement))
+        {
+            findings.push(HygieneFinding::MoveToWorkspace {
+                name: name.to_string(),
+                requirement: highest.clone(),
+                members: declarations,
+            });
+        }
+    }
+    findings
+}
+
+/// Whether `shared` can replace a member's own `requirement`: the same semver
+/// series, with a minimum at least as high.
+fn covers(shared: &str, requirement: &str) -> bool {
+    let lower = |req: &str| VersionReq::parse(req).and_then(|r| r.lower_bound());
+    match (lower(shared), lower(requirement)) {
+        (Some(shared), Some(own)) => {
+            shared >= own && compatible_range(&own).is_some_and(|range| range.matches(&shared))
+        }
+        _ => false,
+    }
+}
+
+/// What `fix` changed, and the entries it left alone.
+#[derive(Debug, Default)]
+pub struct FixOutcome {
+    pub rewritten: usize,
+    pub manifests: usize,
+    pub skipped: Vec<String>,
+}
+
+/// Rewrite the manifests: add `MoveToWorkspace` crates to the root's
+/// `[workspace.dependencies]` and switch the members' entries to `workspace = true`.
+/// `manifests` maps member names (`root` included) to their Cargo.toml.
+pub fn fix(
+    root_manifest: &str,
+    manifests: &HashMap<String, String>,
+    findings: &[HygieneFinding],
+) -> Result<FixOutcome> {
+    let mut documents: BTreeMap<String, DocumentMut> = BTreeMap::new();
+
+    let mut switches: Vec<(&str, &MemberRequirement)> = Vec::new();
+    let mut additions: Vec<(&str, &str)> = Vec::new();
+    for finding in findings {
+        match finding {
+            HygieneFinding::UseWorkspace { name, member, .. } => switches.push((name, member)),
+            HygieneFinding::MoveToWorkspace {
+                name,
+                requirement,
+                members,
+            } => {
+                additions.push((name, requirement));
+                switches.extend(members.iter().map(|member| (name.as_str(), member)));
+            }
+            HygieneFinding::Mismatch { .. } => {}
+        }
+    }
+
+    let mut outcome = FixOutcome::default();
+    let mut moved = Vec::new();
+    for (name, requirement) in additions {
+        // Only move crates whose every member entry can be switched
+        let blocked: Vec<String> = switches
+            .iter()
+            .filter(|(switched, _)| *switched == name)
+            .filter_map(|(_, (member, dep_type, _))| {
+                let document = load(&mut documents, manifests.get(member)?).ok()?;
+                dependency_entries(document, *dep_type, name)
+                    .into_iter()
+                    .find_map(|entry| untouchable(entry))
+                    .map(|key| format!("{name} in {member} (has `{key}`)"))
+            })
+            .collect();
+        if !blocked.is_empty() {
+            outcome.skipped.extend(blocked);
+            continue;
+        }
+        let root = load(&mut documents, root_manifest)?;
+        let workspace_dependencies = root["workspace"]
+            .or_insert(Item::Table(Table::new()))
+            .as_table_mut()
+            .context("`workspace` is not a table")?
+            .entry("dependencies")
+            .or_insert(Item::Table(Table::new()));
+        workspace_dependencies[name] = toml_edit::value(requirement);
+        moved.push(name);
+    }
+
+    // A member may declare the crate in its plain table and a target table;
+    // `use_workspace` switches both at once
+    let mut done: Vec<(&str, &str, DependencyType)> = Vec::new();
+    for (name, (member, dep_type, _)) in switches {
+        if done.contains(&(name, member.as_str(), *dep_type)) {
+            continue;
+        }
+        done.push((name, member.as_str(), *dep_type));
+        let is_move = findings
+            .iter()
+            .any(|f| matches!(f, HygieneFinding::MoveToWorkspace { name: moved_name, .. } if moved_name == name));
+        if is_move && !moved.contains(&name) {
+            continue;
+        }
+        let Some(path) = manifests.get(member) else {
+            outcome
+                .skipped
+                .push(format!("{name} in {member} (manifest not found)"));
+            continue;
+        };
+        match use_workspace(load(&mut documents, path)?, *dep_type, name) {
+            Ok(()) => outcome.rewritten += 1,
+            Err(reason) => outcome
+                .skipped
+                .push(format!("{name} in {member} ({reason})")),
+        }
+    }
+
+    for (path, document) in &documents {
+        let original = fs::read_to_string(path)?;
+        let updated = document.to_string();
+        if updated != original {
+            fs::write(path, updated).with_context(|| format!("Failed to write {path}"))?;
+            outcome.manifests += 1;
+        }
+    }
+    Ok(outcome)
+}
+
+/// The parsed manifest at `path`, read on first use so edits accumulate per file.
+fn load<'a>(
+    documents: &'a mut BTreeMap<String, DocumentMut>,
+    path: &str,
+) -> Result<&'a mut DocumentMut> {
+    match documents.entry(path.to_string()) {
+        Entry::Occupied(entry) => Ok(entry.into_mut()),
+        Entry::Vacant(entry) => {
+            let content =
+                fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+            let document = content
+                .parse::<DocumentMut>()
+                .with_context(|| format!("Failed to parse {path}"))?;
+            Ok(entry.insert(document))
+        }
+    }
+}
+
+fn table_name(dep_type: DependencyType) -> &'static str {
+    match dep_type {
+        DependencyType::Dev => "dev-dependencies",
+        DependencyType::Build => "build-dependencies",
+        DependencyType::Normal | DependencyType::Workspace => "dependencies",
+    }
+}
+
+/// Every `name` entry of the `dep_type` table, `[target.<cfg>.*]` copies included.
+fn dependency_entries<'a>(
+    document: &'a mut DocumentMut,
+    dep_type: DependencyType,
+    name: &str,
+) -> Vec<&'a mut Item> {
+    // `TableLike::get_mut`, unlike `Item::get_mut`, does not insert missing keys
+    fn entry<'a>(item: &'a mut Item, key: &str) -> Option<&'a mut Item> {
+        item.as_table_like_mut()?.get_mut(key)
+    }
+
+    let table = table_name(dep_type);
+    let mut entries = Vec::new();
+    for (key, item) in document.iter_mut() {
+        if key.get() == table {
+            entries.extend(entry(item, name));
+        } else if key.get() == "target"
+            && let Some(targets) = item.as_table_like_mut()
+        {
+            for (_, target) in targets.iter_mut() {
+                entries.extend(entry(target, table).and_then(|t| entry(t, name)));
+            }
+        }
+    }
+    entries
+}
+
+fn untouchable(entry: &Item) -> Option<&'static str> {
+    let has = |key: &str| match entry {
+        Item::Table(table) => table.contains_key(key),
+        Item::Value(Value::InlineTable(table)) => table.contains_key(key),
+        _ => false,
+    };
+    UNTOUCHABLE_KEYS.into_iter().find(|key| has(key))
+}
+
+/// Replace the `version` of every entry for `name`, target tables included,
+/// with `workspace = true`, keeping their other keys.
+fn use_workspace(
+    document: &mut DocumentMut,
+    dep_type: DependencyType,
+    name: &str,
+) -> Result<(), String> {
+    let entries = dependency_entries(document, dep_type, name);
+    if entries.is_empty() {
+        return Err("entry not found".to_string());
+    }
+    if let Some(key) = entries.iter().find_map(|entry| untouchable(entry)) {
+        return Err(format!("has `{key}`"));
+    }
+    entries.into_iter().try_for_each(switch_to_workspace)
+}
+
+fn switch_to_workspace(entry: &mut Item) -> Result<(), String> {
+    match entry {
+        Item::Value(Value::String(_)) => {
+            let mut table = InlineTable::new();
+            table.insert("workspace", true.into());
+            *entry = Item::Value(Value::InlineTable(table));
+        }
+        Item::Value(Value::InlineTable(table)) => {
+            table.remove("version");
+            table.insert("workspace", true.into());
+            table.fmt();
+        }
+        Item::Table(table) => {
+            table.remove("version");
+            table.insert("workspace", toml_edit::value(true));
+        }
+        _ => return Err("unexpected entry".to_string()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declared(
+        name: &str,
+        requirement: &str,
+        dep_type: DependencyType,
+        source: &str,
+    ) -> DeclaredDependency {
+        (
+            name.to_string(),
+            requirement.to_string(),
+            dep_type,
+            source.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_check() {
+        let dependencies = [
+            declared("serde", "1.0.200", DependencyType::Workspace, "root"),
+            declared("serde", "1.0", DependencyType::Normal, "api"),
+            declared("serde", "1.0.210", DependencyType::Normal, "cli"),
+            declared("anyhow", "1", DependencyType::Normal, "api"),
+            declared("anyhow", "1.0.80", DependencyType::Dev, "cli"),
+            declared("rand", "0.8", DependencyType::Normal, "api"),
+            declared("rand", "0.9", DependencyType::Normal, "cli"),
+        ];
+        let findings = check(&dependencies);
+
+        assert!(findings.contains(&HygieneFinding::UseWorkspace {
+            name: "serde".to_string(),
+            member: ("api".to_string(), DependencyType::Normal, "1.0".to_string()),
+            workspace_requirement: "1.0.200".to_string(),
+        }));
+        // cli needs more than the workspace offers
+        assert!(
+            findings
+                .iter()
+                .any(|f| matches!(f, HygieneFinding::Mismatch { name, .. } if name == "serde"))
+        );
+        assert!(findings.iter().any(|f| matches!(
+            f,
+            HygieneFinding::MoveToWorkspace { name, requirement, .. } if name == "anyhow" && requirement == "1.0.80"
+        )));
+        // 0.8 and 0.9 cannot share one requirement
+        assert!(
+            !findings.iter().any(
+                |f| matches!(f, HygieneFinding::MoveToWorkspace { name, .. } if name == "rand")
+            )
+        );
+    }
+
+    #[test]
+    fn test_use_workspace() {
+        let mut document: DocumentMut =
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nlog = \"0.4\"\nlocal = { path = \"../local\", version = \"1\" }\n"
+                .parse()
+                .unwrap();
+        use_workspace(&mut document, DependencyType::Normal, "serde").unwrap();
+        use_workspace(&mut document, DependencyType::Normal, "log").unwrap();
+        assert!(use_workspace(&mut document, DependencyType::Normal, "local").is_err());
+        let rewritten = document.to_string();
+        assert!(rewritten.contains("serde = { features = [\"derive\"], workspace = true }"));
+        assert!(rewritten.contains("log = { workspace = true }"));
+
+        let mut document: DocumentMut =
+            "[dependencies]\nlibc = \"0.2\"\n\n[target.'cfg(unix)'.dependencies]\nlibc = { version = \"0.2.150\", default-features = false }\n\n[target.'cfg(windows)'.dependencies]\nwinapi = \"0.3\"\n"
+                .parse()
+                .unwrap();
+        use_workspace(&mut document, DependencyType::Normal, "winapi").unwrap();
+        assert!(
+            document
+                .to_string()
+                .contains("winapi = { workspace = true }")
+        );
+        // The target copy blocks the plain one too
+        assert!(use_workspace(&mut document, DependencyType::Normal, "libc").is_err());
+        assert!(document.to_string().contains("libc = \"0.2\""));
+    }
+}
@@ -0,0 +1,117 @@
+use toml::Value;
+
+/// Licenses listed under `allowed-licenses` in the root manifest's
+/// `[package.metadata.stale]`, falling back to `[workspace.metadata.stale]`.
+/// `None` when neither sets an allow-list.
+pub fn allowed_licenses(manifest_path: &str) -> Option<Vec<String>> {
+    let (_, toml) = crate::cargo::parser::read_manifest(manifest_path).ok()?;
+    ["package", "workspace"].into_iter().find_map(|table| {
+        let list = toml
+            .get(table)?
+            .get("metadata")?
+            .get("stale")?
+            .get("allowed-licenses")?
+            .as_array()?;
+        Some(
+            list.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        )
+    })
+}
+
+/// Whether an SPDX license expression only needs licenses from `allowed`: some
+/// `OR` alternative must consist of allowed licenses joined by `AND`. Legacy
+/// `MIT/Apache-2.0` slashes count as `OR`; a `WITH` exception stays part of its
+/// license, so `Apache-2.0 WITH LLVM-exception` must be allowed as written.
+/// An expression that does not parse as a whole (`MIT GPL-3.0`, `MIT)`,
+/// lowercase `or`) is not allowed.
+pub fn is_allowed(expression: &str, allowed: &[String]) -> bool {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let mut tokens = spaced.split_whitespace().peekable();
+    let permitted = |license: &str| {
+        license
+            .split('/')
+            .any(|part| allowed.iter().any(|ok| ok.eq_ignore_ascii_case(part)))
+    };
+    let result = any_of(&mut tokens, &permitted);
+    result == Some(true) && tokens.next().is_none()
+}
+
+type Tokens<'a> = std::iter::Peekable<std::str::SplitWhitespace<'a>>;
+
+/// `a OR b OR ...`; `None` when malformed.
+fn any_of(tokens: &mut Tokens, permitted: &dyn Fn(&str) -> bool) -> Option<bool> {
+    let mut result = all_of(tokens, permitted)?;
+    while tokens.next_if_eq(&"OR").is_some() {
+        result |= all_of(tokens, permitted)?;
+    }
+    Some(result)
+}
+
+/// `a AND b AND ...`
+fn all_of(tokens: &mut Tokens, permitted: &dyn Fn(&str) -> bool) -> Option<bool> {
+    let mut result = license(tokens, permitted)?;
+    while tokens.next_if_eq(&"AND").is_some() {
+        result &= license(tokens, permitted)?;
+    }
+    Some(result)
+}
+
+/// A parenthesized expression, or a license with an optional `WITH` exception.
+fn license(tokens: &mut Tokens, permitted: &dyn Fn(&str) -> bool) -> Option<bool> {
+    match tokens.next()? {
+        "(" => {
+            let result = any_of(tokens, permitted)?;
+            tokens.next_if_eq(&")")?;
+            Some(result)
+        }
+        id if is_reserved(id) => None,
+        id => match tokens.next_if_eq(&"WITH") {
+            Some(_) => {
+                let exception = tokens.next().filter(|token| !is_reserved(token))?;
+                Some(permitted(&format!("{id} WITH {exception}")))
+            }
+            None => Some(permitted(id)),
+        },
+    }
+}
+
+/// Operators and parentheses, in any case, can never be a license id.
+fn is_reserved(token: &str) -> bool {
+    ["AND", "OR", "WITH", "(", ")"]
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_allowed() {
+        let allowed = vec![
+            "MIT".to_string(),
+            "Apache-2.0".to_string(),
+            "Apache-2.0 WITH LLVM-exception".to_string(),
+        ];
+        assert!(is_allowed("MIT", &allowed));
+        assert!(is_allowed("MIT OR GPL-3.0", &allowed));
+        assert!(is_allowed("(MIT OR Apache-2.0) AND Apache-2.0", &allowed));
+        assert!(is_allowed("MIT/Apache-2.0", &allowed));
+        assert!(is_allowed("Apache-2.0 WITH LLVM-exception", &allowed));
+        assert!(!is_allowed("GPL-3.0-only", &allowed));
+        assert!(!is_allowed("MIT AND GPL-3.0", &allowed));
+        assert!(!is_allowed("MPL-2.0/GPL-3.0", &allowed));
+        assert!(!is_allowed("(MIT OR GPL-3.0) AND GPL-3.0", &allowed));
+        // Malformed expressions are not allowed, whatever they mention
+        assert!(!is_allowed("MIT GPL-3.0", &allowed));
+        assert!(!is_allowed("MIT)", &allowed));
+        assert!(!is_allowed("(MIT OR Apache-2.0", &allowed));
+        assert!(!is_allowed("MIT or GPL-3.0", &allowed));
+        assert!(!is_allowed("MIT OR and", &allowed));
+        assert!(!is_allowed("Apache-2.0 WITH", &allowed));
+        assert!(!is_allowed("", &allowed));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use crate::api::local_index::IndexReader;
+use crate::api::release_graph::{find_release, latest_release, resolve_tree};
+use crate::cargo::lockfile::{LockGraph, LockedPackage};
+use crate::types::LinksConflict;
+use crate::version::comparison::compatible_range;
+use crate::version::core::Version;
+
+/// Whether `a` and `b` are semver-compatible releases, which Cargo unifies into one.
+fn compatible(a: &str, b: &str) -> bool {
+    Version::parse(a)
+        .and_then(|a| compatible_range(&a))
+        .zip(Version::parse(b))
+        .is_some_and(|(range, b)| range.matches(&b))
+}
+
+/// Native `links` conflicts in and around the lock graph.
+///
+/// `upgrades` are the outdated direct dependencies as (name, locked version,
+/// latest version): each is checked for bringing in a second package linking a
+/// library that something else in the graph keeps linking. Independently, locked
+/// `links` packages shared by several dependents whose latest release is
+/// semver-incompatible are reported as near-conflicts.
+pub fn find_links_conflicts(
+    reader: &IndexReader,
+    packages: &[LockedPackage],
+    upgrades: &[(String, String, String)],
+) -> Vec<LinksConflict> {
+    let graph = LockGraph::new(packages);
+    let links: HashMap<usize, String> = packages
+        .iter()
+        .enumerate()
+        .filter(|(_, package)| package.is_from_registry())
+        .filter_map(|(i, package)| {
+            let krate = reader.read_crate(&package.name).ok()?;
+            let release = krate
+                .versions()
+                .iter()
+                .find(|r| r.version() == package.version)?;
+            Some((i, release.links()?.to_string()))
+        })
+        .collect();
+    if links.is_empty() {
+        return Vec::new();
+    }
+
+    let mut conflicts = Vec::new();
+    for (name, locked, latest) in upgrades {
+        let Some(release) = reader
+            .read_crate(name)
+            .ok()
+            .and_then(|krate| find_release(krate.versions(), latest).cloned())
+        else {
+            continue;
+        };
+        let remaining = graph.reachable_without(graph.find(name, locked));
+        let mut incoming = resolve_tree(reader, &release);
+        incoming.insert(name.clone(), release);
+
+        for (incoming_name, incoming_release) in &incoming {
+            let Some(library) = incoming_release.links() else {
+                continue;
+            };
+            for (&existing, existing_links) in &links {
+                let package = &packages[existing];
+                let unifies = package.name == *incoming_name
+                    && compatible(&package.version, incoming_release.version());
+                if existing_links == library && remaining.contains(&existing) && !unifies {
+                    conflicts.push(LinksConflict::Upgrade {
+                        dependency: name.clone(),
+                        version: latest.clone(),
+                        links: library.to_string(),
+                        incoming: format!("{incoming_name} {}", incoming_release.version()),
+                        existing: graph.label(existing),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut shared: Vec<(&usize, &String)> = links.iter().collect();
+    shared.sort();
+    for (&index, library) in shared {
+        let dependents: Vec<String> = graph
+            .dependents(index)
+            .into_iter()
+            .map(|i| graph.label(i))
+            .collect();
+        if dependents.len() < 2 {
+            continue;
+        }
+        let package = &packages[index];
+        let latest = reader.read_crate(&package.name).ok().and_then(|krate| {
+            latest_release(krate.versions(), false).map(|r| r.version().to_string())
+        });
+        if let Some(latest) = latest.filter(|latest| !compatible(&package.version, latest)) {
+            conflicts.push(LinksConflict::Lockstep {
+                links: library.clone(),
+                package: graph.label(index),
+                latest,
+                dependents,
+            });
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::FixtureIndex;
+
+    fn package(name: &str, version: &str, registry: bool, deps: &[&str]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: registry
+                .then(|| "registry+https://github.com/rust-lang/crates.io-index".to_string()),
+            dependencies: deps.iter().map(|d| (*d).to_string()).collect(),
+        }
+    }
+
+    fn upgrade(name: &str, locked: &str, latest: &str) -> (String, String, String) {
+        (name.to_string(), locked.to_string(), latest.to_string())
+    }
+
+    /// `db` 0.29 and `cache` both link `sqlite3` through `sqlite-sys` 0.26;
+    /// `db` 0.31 moves to `sqlite-sys` 0.28, `db` 0.29.5 stays on 0.26.
+    fn sqlite_index(label: &str) -> FixtureIndex {
+        let index = FixtureIndex::new(label);
+        index
+            .publish("sqlite-sys", "0.26.0", &[], Some("sqlite3"))
+            .publish("sqlite-sys", "0.28.0", &[], Some("sqlite3"))
+            .publish("db", "0.29.0", &[("sqlite-sys", "^0.26")], None)
+            .publish("db", "0.29.5", &[("sqlite-sys", "^0.26")], None)
+            .publish("db", "0.31.0", &[("sqlite-sys", "^0.28")], None)
+            .publish("cache", "1.0.0", &[("sqlite-sys", "^0.26")], None);
+        index
+    }
+
+    #[test]
+    fn test_compatible() {
+        assert!(compatible("0.26.0", "0.26.4"));
+        assert!(!compatible("0.26.0", "0.28.0"));
+        assert!(compatible("1.2.0", "1.9.1"));
+    }
+
+    #[test]
+    fn test_upgrade_brings_second_links_copy() {
+        let index = sqlite_index("links-upgrade");
+        let packages = [
+            package("app", "0.1.0", false, &["db", "cache"]),
+            package("db", "0.29.0", true, &["sqlite-sys"]),
+            package("cache", "1.0.0", true, &["sqlite-sys"]),
+            package("sqlite-sys", "0.26.0", true, &[]),
+        ];
+        let conflicts = find_links_conflicts(
+            &index.reader(),
+            &packages,
+            &[upgrade("db", "0.29.0", "0.31.0")],
+        );
+        assert!(conflicts.contains(&LinksConflict::Upgrade {
+            dependency: "db".to_string(),
+            version: "0.31.0".to_string(),
+            links: "sqlite3".to_string(),
+            incoming: "sqlite-sys 0.28.0".to_string(),
+            existing: "sqlite-sys 0.26.0".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_lockstep_without_upgrades() {
+        let index = sqlite_index("links-lockstep");
+        let packages = [
+            package("app", "0.1.0", false, &["db", "cache"]),
+            package("db", "0.29.0", true, &["sqlite-sys"]),
+            package("cache", "1.0.0", true, &["sqlite-sys"]),
+            package("sqlite-sys", "0.26.0", true, &[]),
+        ];
+        assert_eq!(
+            find_links_conflicts(&index.reader(), &packages, &[]),
+            vec![LinksConflict::Lockstep {
+                links: "sqlite3".to_string(),
+                package: "sqlite-sys 0.26.0".to_string(),
+                latest: "0.28.0".to_string(),
+                dependents: vec!["db 0.29.0".to_string(), "cache 1.0.0".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_compatible_upgrade_does_not_warn() {
+        let index = sqlite_index("links-compatible");
+        // A single dependent, so no lockstep; the upgrade unifies with the locked copy
+        let packages = [
+            package("app", "0.1.0", false, &["db"]),
+            package("db", "0.29.0", true, &["sqlite-sys"]),
+            package("sqlite-sys", "0.26.0", true, &[]),
+        ];
+        let conflicts = find_links_conflicts(
+            &index.reader(),
+            &packages,
+            &[upgrade("db", "0.29.0", "0.29.5")],
+        );
+        assert!(conflicts.is_empty());
+    }
+}
@@ -0,0 +1,433 @@
+use log::warn;
+use std::collections::HashMap;
+use std::fmt;
+use toml::Value;
+
+use crate::types::{DeclaredDependency, DependencyType, PublishedVersion, VersionLookup};
+use crate::version::comparison::compatible_range;
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// A check on a requirement string; the ID is what configuration and inline
+/// suppressions refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// `*`: any version at all, never reported as outdated
+    Wildcard,
+    /// Only lower bounds (`>=1.0`): the next breaking release is already allowed
+    Unbounded,
+    /// `=1.2.3` with no comment explaining the pin
+    ExactPin,
+    /// A `<`/`<=` cap that excludes the latest semver-compatible release
+    UpperBound,
+    /// Nothing published and non-yanked satisfies the requirement
+    Unmatched,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 5] = [
+        Lint::Wildcard,
+        Lint::Unbounded,
+        Lint::ExactPin,
+        Lint::UpperBound,
+        Lint::Unmatched,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Lint::Wildcard => "wildcard-requirement",
+            Lint::Unbounded => "unbounded-requirement",
+            Lint::ExactPin => "unexplained-exact-pin",
+            Lint::UpperBound => "upper-bound-excludes-latest",
+            Lint::Unmatched => "unmatched-requirement",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.id() == id)
+    }
+}
+
+/// How a lint is reported: `deny` findings fail the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    fn parse(level: &str) -> Option<LintLevel> {
+        match level {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None,
+        }
+    }
+
+    pub fn is_deny(self) -> bool {
+        self == LintLevel::Deny
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warning"),
+            LintLevel::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// A lint that fired on a dependency declaration.
+#[derive(Debug, Clone)]
+pub struct LintFinding {
+    pub lint: Lint,
+    pub level: LintLevel,
+    pub path: String,
+    /// 1-based line and column of the declaration, when it could be found
+    pub location: Option<(usize, usize)>,
+    pub name: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, col)) => write!(f, "{}:{line}:{col}: ", self.path)?,
+            None => write!(f, "{}: ", self.path)?,
+        }
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            self.level,
+            self.lint.id(),
+            self.name,
+            self.message
+        )
+    }
+}
+
+/// A manifest's text and the lint levels that apply to its declarations.
+struct LintedManifest {
+    path: String,
+    lines: Vec<String>,
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintedManifest {
+    /// `inherited` are the root's `[workspace.metadata.stale.lints]` levels;
+    /// the manifest's own `[package.metadata.stale.lints]` override them.
+    fn load(path: &str, inherited: &HashMap<Lint, LintLevel>) -> Option<Self> {
+        let (content, toml) = crate::cargo::parser::read_manifest(path).ok()?;
+        let mut levels = inherited.clone();
+        levels.extend(configured_levels(path, &toml, "package"));
+        Some(LintedManifest {
+            path: path.to_string(),
+            lines: content.lines().map(str::to_string).collect(),
+            levels,
+        })
+    }
+
+    /// Level of `lint` for the declaration at `declared`, `None` when allowed.
+    fn level(&self, lint: Lint, declared: Option<usize>) -> Option<LintLevel> {
+        let suppressed = declared.is_some_and(|line| {
+            comments_for(&self.lines, line)
+                .any(|comment| suppressions(comment).any(|id| id == lint.id()))
+        });
+        // Every lint warns unless configured otherwise
+        let level = self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn);
+        (!suppressed && level != LintLevel::Allow).then_some(level)
+    }
+}
+
+/// Levels set under `[<table>.metadata.stale.lints]`, e.g. `unbounded-requirement = "deny"`.
+fn configured_levels(path: &str, toml: &Value, table: &str) -> HashMap<Lint, LintLevel> {
+    let Some(lints) = toml
+        .get(table)
+        .and_then(|t| t.get("metadata"))
+        .and_then(|m| m.get("stale"))
+        .and_then(|s| s.get("lints"))
+        .and_then(Value::as_table)
+    else {
+        return HashMap::new();
+    };
+
+    lints
+        .iter()
+        .filter_map(|(id, level)| {
+            let lint = Lint::from_id(id);
+            let parsed = level.as_str().and_then(LintLevel::parse);
+            if lint.is_none() || parsed.is_none() {
+                warn!("{path}: ignoring lint setting {id} = {level}");
+            }
+            Some((lint?, parsed?))
+        })
+        .collect()
+}
+
+/// Index of the line declaring `name` in the table `dep_type` belongs to, either
+/// as `name = ...` under the table header or as a `[table.name]` header.
+fn declaration_line(lines: &[String], dep_type: DependencyType, name: &str) -> Option<usize> {
+    let table = match dep_type {
+        DependencyType::Normal => "dependencies",
+        DependencyType::Dev => "dev-dependencies",
+        DependencyType::Build => "build-dependencies",
+        DependencyType::Workspace => "workspace.dependencies",
+    };
+    let mut in_table = false;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or_default().trim();
+            if header == format!("{table}.{name}") {
+                return Some(index);
+            }
+            in_table = header == table;
+        } else if in_table
+            && trimmed
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// The comments attached to the declaration at `line`: trailing on that line,
+/// or on the lines directly above it.
+fn comments_for(lines: &[String], line: usize) -> impl Iterator<Item = &str> {
+    let trailing = comment_start(&lines[line]).map(|start| &lines[line][start + 1..]);
+    let above = lines[..line]
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with('#'))
+        .map(|l| &l[1..]);
+    trailing.into_iter().chain(above)
+}
+
+/// Byte offset of the `#` opening a line's comment, skipping any inside strings
+/// such as `git = "https://host/repo#branch"`.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(open), _) if c == open && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return Some(index),
+            _ => {}
+        }
+        escaped = false;
+    }
+    None
+}
+
+/// Lint IDs named by `stale:allow(id, ...)` in a comment.
+fn suppressions(comment: &str) -> impl Iterator<Item = &str> {
+    comment
+        .split("stale:allow(")
+        .skip(1)
+        .filter_map(|rest| rest.split_once(')'))
+        .flat_map(|(ids, _)| ids.split(','))
+        .map(str::trim)
+}
+
+/// Run every requirement lint over the declared dependencies. `manifests` maps
+/// each source (`root` included) to its Cargo.toml; lints needing published
+/// releases skip crates `lookup` does not know. When the releases come from
+/// Cargo's local index cache, which lags behind the registry, a requirement no
+/// known release matches is not reported.
+pub fn check_requirements(
+    dependencies: &[DeclaredDependency],
+    manifests: &HashMap<String, String>,
+    lookup: &VersionLookup,
+) -> Vec<LintFinding> {
+    let inherited = manifests
+        .get("root")
+        .and_then(|root| {
+            let (_, toml) = crate::cargo::parser::read_manifest(root).ok()?;
+            Some(configured_levels(root, &toml, "workspace"))
+        })
+        .unwrap_or_default();
+    let mut loaded: HashMap<&str, Option<LintedManifest>> = HashMap::new();
+
+    let mut findings = Vec::new();
+    for (name, requirement, dep_type, source) in dependencies {
+        let Some(manifest) = loaded
+            .entry(source)
+            .or_insert_with(|| {
+                manifests
+                    .get(source)
+                    .and_then(|path| LintedManifest::load(path, &inherited))
+            })
+            .as_ref()
+        else {
+            continue;
+        };
+        let line = declaration_line(&manifest.lines, *dep_type, name);
+        let commented =
+            line.is_some_and(|line| comments_for(&manifest.lines, line).next().is_some());
+        let published = lookup.versions.get(name).map(Vec::as_slice);
+
+        for (lint, message) in requirement_problems(requirement, commented, published)
+            .into_iter()
+            .filter(|(lint, _)| !lookup.from_local_cache || *lint != Lint::Unmatched)
+        {
+            if let Some(level) = manifest.level(lint, line) {
+                findings.push(LintFinding {
+                    lint,
+                    level,
+                    path: manifest.path.clone(),
+                    location: line.map(|line| {
+                        let indent =
+                            manifest.lines[line].len() - manifest.lines[line].trim_start().len();
+                        (line + 1, indent + 1)
+                    }),
+                    name: name.clone(),
+                    message,
+                });
+            }
+        }
+    }
+    findings
+}
+
+/// The lints `requirement` trips, before levels and suppressions.
+fn requirement_problems(
+    requirement: &str,
+    commented: bool,
+    published: Option<&[PublishedVersion]>,
+) -> Vec<(Lint, String)> {
+    let Some(req) = VersionReq::parse(requirement) else {
+        return vec![(
+            Lint::Unmatched,
+            format!("`{requirement}` is not a valid version requirement"),
+        )];
+    };
+
+    let mut problems = Vec::new();
+    if req.is_wildcard() {
+        problems.push((
+            Lint::Wildcard,
+            "`*` accepts any version, so it is never reported as outdated".to_string(),
+        ));
+    } else if req.is_unbounded() {
+        problems.push((
+            Lint::Unbounded,
+            format!("`{requirement}` has no upper bound and allows future breaking releases"),
+        ));
+    }
+    if req.pins_exact() && !commented {
+        problems.push((
+            Lint::ExactPin,
+            format!("`{requirement}` pins an exact version; add a comment saying why"),
+        ));
+    }
+
+    let Some(published) = published.filter(|p| !p.is_empty()) else {
+        return problems;
+    };
+    let releases: Vec<Version> = published
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Version::parse(&v.num))
+        .collect();
+    if !releases.iter().any(|v| req.matches(v)) {
+        problems.push((
+            Lint::Unmatched,
+            format!("no published, non-yanked version matches `{requirement}`"),
+        ));
+    } else if req.has_upper_bound()
+        && let Some(lower) = req.lower_bound()
+        && let Some(compatible) = compatible_range(&lower)
+        && let Some(latest) = releases
+            .iter()
+            .filter(|v| v.pre_release.is_none() && compatible.matches(v))
+            .max()
+        && !req.matches(latest)
+    {
+        problems.push((
+            Lint::UpperBound,
+            format!(
+                "`{requirement}` excludes {latest}, the latest release compatible with {lower}"
+            ),
+        ));
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn published(nums: &[&str]) -> Vec<PublishedVersion> {
+        nums.iter()
+            .map(|num| PublishedVersion {
+                num: (*num).to_string(),
+                ..PublishedVersion::default()
+            })
+            .collect()
+    }
+
+    fn lints(requirement: &str, commented: bool, versions: &[PublishedVersion]) -> Vec<Lint> {
+        requirement_problems(requirement, commented, Some(versions))
+            .into_iter()
+            .map(|(lint, _)| lint)
+            .collect()
+    }
+
+    #[test]
+    fn test_requirement_problems() {
+        let versions = published(&["1.0.0", "1.4.0", "1.7.2", "2.0.0"]);
+        assert_eq!(lints("*", false, &versions), [Lint::Wildcard]);
+        assert_eq!(lints(">=1.0", false, &versions), [Lint::Unbounded]);
+        assert_eq!(lints("=1.4.0", false, &versions), [Lint::ExactPin]);
+        assert!(lints("=1.4.0", true, &versions).is_empty());
+        assert_eq!(lints(">=1.0, <1.5", false, &versions), [Lint::UpperBound]);
+        assert!(lints(">=1.0, <1.8", false, &versions).is_empty());
+        assert_eq!(lints("3", false, &versions), [Lint::Unmatched]);
+        assert!(lints("1.4", false, &versions).is_empty());
+    }
+
+    #[test]
+    fn test_comment_start() {
+        assert_eq!(comment_start("a = \"1\" # why"), Some(8));
+        assert_eq!(comment_start("a = { git = \"https://h/r#x\" }"), None);
+        assert_eq!(comment_start("a = '#1' # literal"), Some(9));
+        assert_eq!(comment_start(r##"a = "\"#" # escaped"##), Some(10));
+    }
+
+    #[test]
+    fn test_declaration_comments() {
+        let lines: Vec<String> = "[dependencies]\n# stale:allow(unbounded-requirement)\ntokio = \">=1\"\nlog = \"=0.4.20\" # matches the vendored copy\nfork = { git = \"https://example.com/fork#main\", version = \"=1.0.0\" }\n\n[dev-dependencies.serde]\nversion = \"1\"\n"
+            .lines()
+            .map(str::to_string)
+            .collect();
+        let tokio = declaration_line(&lines, DependencyType::Normal, "tokio").unwrap();
+        assert!(
+            comments_for(&lines, tokio)
+                .any(|c| suppressions(c).any(|id| id == "unbounded-requirement"))
+        );
+        let log = declaration_line(&lines, DependencyType::Normal, "log").unwrap();
+        assert_eq!(comments_for(&lines, log).count(), 1);
+        let fork = declaration_line(&lines, DependencyType::Normal, "fork").unwrap();
+        assert_eq!(comments_for(&lines, fork).count(), 0);
+        assert_eq!(
+            declaration_line(&lines, DependencyType::Dev, "serde"),
+            Some(6)
+        );
+        assert_eq!(
+            declaration_line(&lines, DependencyType::Normal, "serde"),
+            None
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::api::advisory_db::AdvisoryDb;
+use crate::types::{PublishedVersion, VulnerablePackage, YankedPackage};
+use crate::version::comparison::nearest_replacement;
+
+#[derive(Debug, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+/// A `[[package]]` entry of Cargo.lock.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// `name`, or `name version` when several versions are locked
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Whether the package comes from a registry (as opposed to a path or git dependency).
+    pub fn is_from_registry(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|s| s.starts_with("registry+") || s.starts_with("sparse+"))
+    }
+}
+
+/// Cargo.lock as a graph: the packages, and for each the indices of its dependencies.
+pub struct LockGraph<'a> {
+    pub packages: &'a [LockedPackage],
+    edges: Vec<Vec<usize>>,
+}
+
+impl<'a> LockGraph<'a> {
+    pub fn new(packages: &'a [LockedPackage]) -> Self {
+        let resolve = |entry: &str| {
+            let mut parts = entry.split(' ');
+            let name = parts.next()?;
+            let version = parts.next();
+            packages
+                .iter()
+                .position(|p| p.name == name && version.is_none_or(|v| p.version == v))
+        };
+        let edges = packages
+            .iter()
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter_map(|entry| resolve(entry))
+                    .collect()
+            })
+            .collect();
+        LockGraph { packages, edges }
+    }
+
+    pub fn find(&self, name: &str, version: &str) -> Option<usize> {
+        self.packages
+            .iter()
+            .position(|p| p.name == name && p.version == version)
+    }
+
+    /// Packages still reachable from the workspace members once they stop
+    /// depending on `dropped` directly.
+    pub fn reachable_without(&self, dropped: Option<usize>) -> HashSet<usize> {
+        let mut queue: VecDeque<usize> = (0..self.packages.len())
+            .filter(|&i| self.packages[i].source.is_none())
+            .collect();
+        let mut seen: HashSet<usize> = queue.iter().copied().collect();
+        while let Some(current) = queue.pop_front() {
+            let is_member = self.packages[current].source.is_none();
+            for &next in &self.edges[current] {
+                if is_member && Some(next) == dropped {
+                    continue;
+                }
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Packages depending directly on `index`.
+    pub fn dependents(&self, index: usize) -> Vec<usize> {
+        (0..self.packages.len())
+            .filter(|&i| self.edges[i].contains(&index))
+            .collect()
+    }
+
+    pub fn label(&self, index: usize) -> String {
+        let package = &self.packages[index];
+        format!("{} {}", package.name, package.version)
+    }
+}
+
+/// Find the Cargo.lock governing a manifest by walking up from its directory.
+pub fn find_lockfile(manifest_path: &str) -> Option<PathBuf> {
+    let manifest_path = crate::utils::ensure_cargo_toml_path(manifest_path);
+    let start = Path::new(manifest_path.as_ref())
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    start
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|candidate| candidate.is_file())
+}
+
+pub fn read_lockfile(manifest_path: &str) -> Result<Vec<LockedPackage>> {
+    let path = find_lockfile(manifest_path)
+        .with_context(|| format!("No Cargo.lock found for {manifest_path}"))?;
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let lockfile: Lockfile =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(lockfile.package)
+}
+
+/// Locked registry packages whose exact version has been yanked, with the nearest
+/// compatible release to move to. Packages without a known version list are skipped.
+pub fn find_yanked(
+    locked: &[LockedPackage],
+    versions: &HashMap<String, Vec<PublishedVersion>>,
+) -> Vec<YankedPackage> {
+    locked
+        .iter()
+        .filter(|package| package.is_from_registry())
+        .filter_map(|package| {
+            let published = versions.get(&package.name)?;
+            published
+                .iter()
+                .any(|v| v.yanked && v.num == package.version)
+                .then(|| YankedPackage {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    replacement: nearest_replacement(published, &package.version)
+                        .map(str::to_string),
+                })
+        })
+        .collect()
+}
+
+/// Locked registry packages whose exact version has `RustSec` advisories.
+pub fn find_vulnerable(
+    locked: &[LockedPackage],
+    versions: &HashMap<String, Vec<PublishedVersion>>,
+    advisories: &AdvisoryDb,
+) -> Vec<VulnerablePackage> {
+    locked
+        .iter()
+        .filter(|package| package.is_from_registry())
+        .filter_map(|package| {
+            let matches = advisories.check(
+                &package.name,
+                &package.version,
+                versions.get(&package.name).map(Vec::as_slice),
+            );
+            (!matches.is_empty()).then(|| VulnerablePackage {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                advisories: matches,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, registry: bool, deps: &[&str]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: registry
+                .then(|| "registry+https://github.com/rust-lang/crates.io-index".to_string()),
+            dependencies: deps.iter().map(|d| (*d).to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_reachable_without() {
+        let packages = [
+            package("app", "0.1.0", false, &["db", "cache"]),
+            package("db", "0.29.0", true, &["sqlite-sys"]),
+            package("cache", "1.0.0", true, &["sqlite-sys"]),
+            package("sqlite-sys", "0.26.0", true, &[]),
+        ];
+        let graph = LockGraph::new(&packages);
+        // Dropping `db` leaves `sqlite-sys` linked through `cache`
+        let remaining = graph.reachable_without(graph.find("db", "0.29.0"));
+        assert!(!remaining.contains(&1));
+        assert!(remaining.contains(&3));
+        assert_eq!(graph.dependents(3), vec![1, 2]);
+    }
+}
@@ -1,2 +1,10 @@
+pub mod consistency;
+pub mod diagnostics;
+pub mod duplicates;
+pub mod hygiene;
+pub mod licenses;
+pub mod links;
+pub mod lints;
+pub mod lockfile;
 pub mod parser;
 pub mod workspace;
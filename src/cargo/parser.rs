@@ -1,20 +1,38 @@
-use anyhow::{Context, Result};
 use std::fs;
 use toml::Value;
 
-use crate::types::DependencyType;
+use crate::cargo::diagnostics::{self, Diagnostic};
+use crate::types::{DeclaredDependency, DependencyType, FeatureRequest, TableRequirement};
 
+/// Read and parse a manifest, describing any failure as a positioned diagnostic.
+pub fn read_manifest(path: &str) -> Result<(String, Value), Diagnostic> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Diagnostic::error(path, format!("failed to read manifest: {e}")))?;
+    let toml = toml::from_str(&content).map_err(|e| Diagnostic::from_toml(path, &content, &e))?;
+    Ok((content, toml))
+}
+
+/// Collect the dependencies of one manifest. Entries that cannot be understood
+/// are skipped and reported in `diagnostics`; only an unreadable or unparsable
+/// manifest is an error.
 pub fn parse_cargo_toml(
     path: &str,
     include_build: bool,
     source_name: &str,
-) -> Result<Vec<(String, String, DependencyType, String)>> {
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<DeclaredDependency>, Diagnostic> {
     // Ensure the path is a valid Cargo.toml file
     let path = crate::utils::ensure_cargo_toml_path(path);
-    let content = fs::read_to_string(path.as_ref())
-        .with_context(|| format!("Failed to read file: {path}"))?;
+    let (content, toml) = read_manifest(path.as_ref())?;
+    let mut check = |name: &str, value: &Value| {
+        if let Some(problem) = shape_problem(value) {
+            diagnostics.push(
+                Diagnostic::warning(path.as_ref(), format!("dependency '{name}': {problem}"))
+                    .at(diagnostics::find_key(&content, name)),
+            );
+        }
+    };
 
-    let toml: Value = toml::from_str(&content).with_context(|| "Failed to parse Cargo.toml")?;
     let mut dependencies = Vec::new();
     let mut workspace_versions = std::collections::HashMap::new();
     if let Some(workspace_deps) = toml
@@ -23,6 +41,7 @@ pub fn parse_cargo_toml(
         .and_then(|v| v.as_table())
     {
         for (name, value) in workspace_deps {
+            check(name, value);
             if let Some(version) = extract_version_only(value) {
                 workspace_versions.insert(name.clone(), version.clone());
                 dependencies.push((
@@ -38,6 +57,7 @@ pub fn parse_cargo_toml(
     // Parse [dependencies]
     if let Some(deps) = toml.get("dependencies").and_then(|v| v.as_table()) {
         for (name, value) in deps {
+            check(name, value);
             if let Some(version) = extract_version_with_workspace(value, &workspace_versions) {
                 dependencies.push((
                     name.clone(),
@@ -52,6 +72,7 @@ pub fn parse_cargo_toml(
     // Parse [dev-dependencies]
     if let Some(dev_deps) = toml.get("dev-dependencies").and_then(|v| v.as_table()) {
         for (name, value) in dev_deps {
+            check(name, value);
             if let Some(version) = extract_version_with_workspace(value, &workspace_versions) {
                 dependencies.push((
                     name.clone(),
@@ -68,6 +89,7 @@ pub fn parse_cargo_toml(
         && let Some(build_deps) = toml.get("build-dependencies").and_then(|v| v.as_table())
     {
         for (name, value) in build_deps {
+            check(name, value);
             if let Some(version) = extract_version_with_workspace(value, &workspace_versions) {
                 dependencies.push((
                     name.clone(),
@@ -82,6 +104,184 @@ pub fn parse_cargo_toml(
     Ok(dependencies)
 }
 
+/// Dependencies declared in a manifest's `[target.<cfg>.*]` tables, which
+/// `parse_cargo_toml` leaves out. Like there, `workspace = true` entries are
+/// skipped and an unreadable manifest yields nothing.
+pub fn target_dependencies(
+    path: &str,
+    include_build: bool,
+    source_name: &str,
+) -> Vec<DeclaredDependency> {
+    let path = crate::utils::ensure_cargo_toml_path(path);
+    let Ok((_, toml)) = read_manifest(path.as_ref()) else {
+        return Vec::new();
+    };
+    let no_workspace = std::collections::HashMap::new();
+
+    let mut dependencies = Vec::new();
+    for target_tables in toml
+        .get("target")
+        .and_then(Value::as_table)
+        .into_iter()
+        .flat_map(|targets| targets.values())
+    {
+        for (table, dep_type) in [
+            ("dependencies", DependencyType::Normal),
+            ("dev-dependencies", DependencyType::Dev),
+            ("build-dependencies", DependencyType::Build),
+        ] {
+            if dep_type == DependencyType::Build && !include_build {
+                continue;
+            }
+            for (name, value) in target_tables
+                .get(table)
+                .and_then(Value::as_table)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(version) = extract_version_with_workspace(value, &no_workspace) {
+                    dependencies.push((name.clone(), version, dep_type, source_name.to_string()));
+                }
+            }
+        }
+    }
+    dependencies
+}
+
+/// Features each dependency of a manifest enables, with the requirement they
+/// apply to. `workspace = true` entries add their features to the requirement
+/// of the matching `workspace_versions` entry. An unreadable manifest yields
+/// nothing: `parse_cargo_toml` already reported it.
+pub fn requested_features(
+    path: &str,
+    include_build: bool,
+    source_name: &str,
+    workspace_versions: &std::collections::HashMap<String, String>,
+) -> Vec<FeatureRequest> {
+    let path = crate::utils::ensure_cargo_toml_path(path);
+    let Ok((_, toml)) = read_manifest(path.as_ref()) else {
+        return Vec::new();
+    };
+
+    let workspace_tables = toml
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .into_iter();
+    let tables = ["dependencies", "dev-dependencies", "build-dependencies"]
+        .into_iter()
+        .filter(|table| include_build || *table != "build-dependencies")
+        .filter_map(|table| toml.get(table))
+        .chain(workspace_tables)
+        .filter_map(Value::as_table);
+
+    let mut requests = Vec::new();
+    for (name, value) in tables.flatten() {
+        let Some(table) = value.as_table() else {
+            continue;
+        };
+        let features: Vec<String> = table
+            .get("features")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        let requirement = if table.get("workspace").and_then(Value::as_bool) == Some(true) {
+            workspace_versions.get(name).cloned()
+        } else {
+            table
+                .get("version")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+        if let Some(requirement) = requirement.filter(|_| !features.is_empty()) {
+            requests.push(FeatureRequest {
+                name: name.clone(),
+                requirement,
+                features,
+                source: source_name.to_string(),
+            });
+        }
+    }
+    requests
+}
+
+/// Every registry requirement in a manifest's dependency tables, including
+/// `[target.<cfg>.*]` tables and renamed entries, keyed by the package they name.
+/// `workspace = true` entries take their requirement from `workspace_versions`.
+/// An unreadable manifest yields nothing: `parse_cargo_toml` already reported it.
+pub fn table_requirements(
+    path: &str,
+    workspace_versions: &std::collections::HashMap<String, String>,
+) -> Vec<TableRequirement> {
+    const TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+    let path = crate::utils::ensure_cargo_toml_path(path);
+    let Ok((_, toml)) = read_manifest(path.as_ref()) else {
+        return Vec::new();
+    };
+
+    let mut tables: Vec<(String, &Value)> = TABLES
+        .iter()
+        .filter_map(|table| Some(((*table).to_string(), toml.get(table)?)))
+        .collect();
+    for (target, target_tables) in toml
+        .get("target")
+        .and_then(Value::as_table)
+        .into_iter()
+        .flatten()
+    {
+        let target = if target
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            target.clone()
+        } else {
+            format!("'{target}'")
+        };
+        tables.extend(TABLES.iter().filter_map(|table| {
+            Some((
+                format!("target.{target}.{table}"),
+                target_tables.get(table)?,
+            ))
+        }));
+    }
+
+    let mut requirements = Vec::new();
+    for (table, entries) in tables {
+        for (name, value) in entries.as_table().into_iter().flatten() {
+            let package = value.get("package").and_then(Value::as_str).unwrap_or(name);
+            let requirement = if value.get("workspace").and_then(Value::as_bool) == Some(true) {
+                workspace_versions.get(name).cloned()
+            } else {
+                extract_version_only(value)
+            };
+            if let Some(requirement) = requirement {
+                requirements.push(TableRequirement {
+                    package: package.to_string(),
+                    table: table.clone(),
+                    requirement,
+                });
+            }
+        }
+    }
+    requirements
+}
+
+/// Why a dependency entry cannot be read, if it is malformed. Entries without a
+/// version (path or git dependencies) are fine: they are just not checked.
+fn shape_problem(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::String(_) => None,
+        Value::Table(table) => match table.get("version") {
+            None | Some(Value::String(_)) => None,
+            Some(_) => Some("`version` must be a string"),
+        },
+        _ => Some("expected a version string or a table"),
+    }
+}
+
 fn extract_version_only(value: &Value) -> Option<String> {
     match value {
         Value::String(version) => Some(version.clone()),
@@ -1,14 +1,20 @@
-use anyhow::{Context, Result};
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use toml::Value;
 
-pub fn get_workspace_members(manifest_path: &str) -> Result<Vec<String>> {
+use crate::cargo::diagnostics::{self, Diagnostic};
+
+/// Manifest paths of the workspace members. Members that do not exist are
+/// skipped and reported in `diagnostics`.
+pub fn get_workspace_members(
+    manifest_path: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Vec<String>, Diagnostic> {
     // Ensure the manifest path is a valid Cargo.toml file
     let manifest_path = crate::utils::ensure_cargo_toml_path(manifest_path);
-    let content = fs::read_to_string(manifest_path.as_ref())
-        .with_context(|| format!("Failed to read file: {manifest_path}"))?;
-
-    let toml: Value = toml::from_str(&content).with_context(|| "Failed to parse Cargo.toml")?;
+    let (content, toml) = crate::cargo::parser::read_manifest(manifest_path.as_ref())?;
 
     let mut members = Vec::new();
 
@@ -20,11 +26,51 @@ pub fn get_workspace_members(manifest_path: &str) -> Result<Vec<String>> {
             .unwrap_or(Path::new("."));
 
         for member in member_list {
-            if let Some(member_str) = member.as_str() {
-                let member_path = base_dir.join(member_str).join("Cargo.toml");
-                if member_path.exists() {
-                    members.push(member_path.to_string_lossy().to_string());
+            let Some(member_str) = member.as_str() else {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        manifest_path.as_ref(),
+                        format!("workspace member {member} is not a string"),
+                    )
+                    .at(diagnostics::find(&content, "members")),
+                );
+                continue;
+            };
+            let location = diagnostics::find(&content, &format!("\"{member_str}\""));
+
+            if member_str.contains('*') {
+                let matched = expand_member_glob(base_dir, member_str);
+                if matched.is_empty() {
+                    diagnostics.push(
+                        Diagnostic::warning(
+                            manifest_path.as_ref(),
+                            format!("workspace member pattern '{member_str}' matches no packages"),
+                        )
+                        .at(location),
+                    );
                 }
+                members.extend(
+                    matched
+                        .into_iter()
+                        .map(|path| path.to_string_lossy().to_string()),
+                );
+                continue;
+            }
+
+            let member_path = base_dir.join(member_str).join("Cargo.toml");
+            if member_path.exists() {
+                members.push(member_path.to_string_lossy().to_string());
+            } else {
+                diagnostics.push(
+                    Diagnostic::error(
+                        manifest_path.as_ref(),
+                        format!(
+                            "workspace member '{member_str}' not found: {} does not exist",
+                            member_path.display()
+                        ),
+                    )
+                    .at(location),
+                );
             }
         }
     }
@@ -32,6 +78,35 @@ pub fn get_workspace_members(manifest_path: &str) -> Result<Vec<String>> {
     Ok(members)
 }
 
+/// Manifests of the packages matched by a member pattern whose last component
+/// holds the wildcard, e.g. `crates/*` or `crates/tool-*`.
+fn expand_member_glob(base_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let pattern = Path::new(pattern);
+    let (Some(parent), Some(last)) = (pattern.parent(), pattern.file_name()) else {
+        return Vec::new();
+    };
+    let last = last.to_string_lossy();
+    let Some((prefix, suffix)) = last.split_once('*') else {
+        return Vec::new();
+    };
+
+    let mut matched: Vec<PathBuf> = fs::read_dir(base_dir.join(parent))
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        })
+        .map(|entry| entry.path().join("Cargo.toml"))
+        .filter(|manifest| manifest.exists())
+        .collect();
+    matched.sort();
+    matched
+}
+
 pub fn get_crate_name(manifest_path: &str) -> String {
     if let Ok(content) = fs::read_to_string(manifest_path)
         && let Ok(toml) = toml::from_str::<Value>(&content)
@@ -48,3 +123,89 @@ pub fn get_crate_name(manifest_path: &str) -> String {
         .unwrap_or("unknown")
         .to_string()
 }
+
+/// The `rust-version` a package declares, resolving `rust-version.workspace = true`
+/// against `[workspace.package]` of the enclosing workspace.
+pub fn package_rust_version(manifest_path: &str) -> Option<String> {
+    let manifest_path = crate::utils::ensure_cargo_toml_path(manifest_path);
+    let (_, toml) = crate::cargo::parser::read_manifest(manifest_path.as_ref()).ok()?;
+    match toml.get("package")?.get("rust-version")? {
+        Value::String(version) => Some(version.clone()),
+        Value::Table(table) if table.get("workspace").and_then(Value::as_bool) == Some(true) => {
+            workspace_rust_version(manifest_path.as_ref())
+        }
+        _ => None,
+    }
+}
+
+/// `workspace.package.rust-version` of the workspace enclosing a manifest: the
+/// manifest itself if it has a `[workspace]` table, otherwise the nearest ancestor's.
+pub fn workspace_rust_version(manifest_path: &str) -> Option<String> {
+    let manifest_path = crate::utils::ensure_cargo_toml_path(manifest_path);
+    let manifest = fs::canonicalize(manifest_path.as_ref()).ok()?;
+    let workspace = manifest
+        .parent()?
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .filter_map(|path| crate::cargo::parser::read_manifest(&path.to_string_lossy()).ok())
+        .find_map(|(_, toml)| toml.get("workspace").cloned())?;
+    workspace
+        .get("package")?
+        .get("rust-version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::local_index::fixture::TempDir;
+    use crate::cargo::diagnostics::Severity;
+
+    #[test]
+    fn test_workspace_members() {
+        let dir = TempDir::new("workspace-members");
+        let root = dir.path();
+        let manifest = root.join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[workspace]\nmembers = [\n    \"app\",\n    \"missing\",\n    \"crates/*\",\n]\n",
+        )
+        .unwrap();
+        for member in ["app", "crates/a", "crates/b"] {
+            fs::create_dir_all(root.join(member)).unwrap();
+            fs::write(root.join(member).join("Cargo.toml"), "[package]\n").unwrap();
+        }
+        // Not a package, so the pattern skips it
+        fs::create_dir_all(root.join("crates/notes")).unwrap();
+
+        let mut diagnostics = Vec::new();
+        let members = get_workspace_members(&manifest.to_string_lossy(), &mut diagnostics).unwrap();
+        let members: Vec<PathBuf> = members
+            .iter()
+            .map(|member| Path::new(member).strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            members,
+            ["app", "crates/a", "crates/b"].map(|member| Path::new(member).join("Cargo.toml"))
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].message.contains("'missing' not found"));
+        assert_eq!(diagnostics[0].location, Some((4, 5)));
+    }
+
+    #[test]
+    fn test_member_pattern_without_matches() {
+        let dir = TempDir::new("workspace-pattern");
+        let manifest = dir.path().join("Cargo.toml");
+        fs::write(&manifest, "[workspace]\nmembers = [\"tools/*\"]\n").unwrap();
+
+        let mut diagnostics = Vec::new();
+        let members = get_workspace_members(&manifest.to_string_lossy(), &mut diagnostics).unwrap();
+        assert!(members.is_empty());
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].location, Some((2, 12)));
+    }
+}
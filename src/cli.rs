@@ -1,4 +1,7 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use crate::api::vendor::VendorSource;
 
 #[derive(Parser)]
 #[command(name = "cargo")]
@@ -55,12 +58,135 @@ impl OutputVerbosity {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum PrereleasePolicy {
+    Exclude,
+    Include,
+}
+
+impl PrereleasePolicy {
+    pub fn includes_prereleases(self) -> bool {
+        matches!(self, PrereleasePolicy::Include)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DiagnosticsPolicy {
+    Report,
+    Strict,
+}
+
+impl DiagnosticsPolicy {
+    pub fn is_strict(self) -> bool {
+        matches!(self, DiagnosticsPolicy::Strict)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AdvisoryPolicy {
+    Report,
+    Fail,
+}
+
+impl AdvisoryPolicy {
+    pub fn fails(self) -> bool {
+        matches!(self, AdvisoryPolicy::Fail)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum YankedPolicy {
+    Report,
+    Fail,
+}
+
+impl YankedPolicy {
+    pub fn fails(self) -> bool {
+        matches!(self, YankedPolicy::Fail)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LookupFailurePolicy {
+    Report,
+    Fail,
+}
+
+impl LookupFailurePolicy {
+    pub fn fails(self) -> bool {
+        matches!(self, LookupFailurePolicy::Fail)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TransitivePreview {
+    Hidden,
+    Shown,
+}
+
+impl TransitivePreview {
+    pub fn is_shown(self) -> bool {
+        matches!(self, TransitivePreview::Shown)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum LicenseCheck {
+    Skipped,
+    Run,
+}
+
+impl LicenseCheck {
+    pub fn is_run(self) -> bool {
+        matches!(self, LicenseCheck::Run)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PublisherCheck {
+    Skipped,
+    Run,
+}
+
+impl PublisherCheck {
+    pub fn is_run(self) -> bool {
+        matches!(self, PublisherCheck::Run)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SemverCheckPolicy {
+    Skipped,
+    Run,
+}
+
+impl SemverCheckPolicy {
+    pub fn is_run(self) -> bool {
+        matches!(self, SemverCheckPolicy::Run)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum UpgradeExplanation {
+    Hidden,
+    Shown,
+}
+
+impl UpgradeExplanation {
+    pub fn is_shown(self) -> bool {
+        matches!(self, UpgradeExplanation::Shown)
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(version, about = "Check for outdated dependencies in Cargo.toml")]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to Cargo.toml file
-    #[arg(short, long, default_value = "Cargo.toml")]
+    #[arg(short, long, default_value = "Cargo.toml", global = true)]
     pub manifest: String,
 
     /// Show only outdated dependencies
@@ -68,22 +194,148 @@ pub struct Cli {
     outdated_only: bool,
 
     /// Include build dependencies
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     build_deps: bool,
 
     /// Include workspace members
-    #[arg(short, long, default_value_t = true, action = clap::ArgAction::Set, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(short, long, default_value_t = true, action = clap::ArgAction::Set, num_args = 0..=1, require_equals = true, default_missing_value = "true", global = true)]
     workspace: bool,
 
+    /// Consider pre-release versions when looking for the latest version
+    #[arg(long, global = true)]
+    pre: bool,
+
     /// Force online mode (use crates.io API instead of local index)
-    #[arg(long)]
+    #[arg(long, global = true)]
     online: bool,
 
+    /// Base URL of the crates.io web API, e.g. a mirror or a local stand-in
+    #[arg(long, value_name = "URL", default_value = crate::api::crates_io::DEFAULT_API_URL, global = true)]
+    registry_api: String,
+
+    /// Read index entries from a directory laid out like a sparse index (e.g. `3/s/syn`)
+    #[arg(long, value_name = "DIR", conflicts_with = "online", global = true)]
+    index_path: Option<String>,
+
+    /// Run fully offline against a snapshot written by `snapshot export`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["online", "index_path"], global = true)]
+    snapshot: Option<String>,
+
+    /// Use a `cargo vendor` directory as the version source
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["online", "index_path", "snapshot"], global = true)]
+    vendor: Option<String>,
+
+    /// Use a local registry (`source.<name>.local-registry`) as the version source
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["online", "index_path", "snapshot", "vendor"], global = true)]
+    local_registry: Option<String>,
+
+    /// Local copy of the crates.io `db-dump.tar.gz` for release dates, downloads and metadata
+    #[arg(long, value_name = "FILE")]
+    db_dump: Option<String>,
+
+    /// Exit with an error when any dependency lookup failed (network, rate limit, index errors)
+    #[arg(long)]
+    fail_on_lookup_error: bool,
+
+    /// Check against a local `RustSec` advisory-db clone [default: $CARGO_HOME/advisory-db]
+    #[arg(long, value_name = "DIR", num_args = 0..=1)]
+    #[allow(clippy::option_option)] // absent, given without a directory, or given one
+    advisory_db: Option<Option<String>>,
+
+    /// Exit with an error when a required or locked version has a vulnerability advisory
+    #[arg(long)]
+    fail_on_advisory: bool,
+
+    /// Exit with an error when a required or locked version has been yanked
+    #[arg(long)]
+    fail_on_yanked: bool,
+
+    /// Add a column with the net number of crates each upgrade adds to the dependency tree
+    #[arg(long)]
+    transitive: bool,
+
+    /// Explain what changes between the current and latest version of each outdated dependency
+    #[arg(long)]
+    explain: bool,
+
+    /// Run `cargo semver-checks` between the current and latest version of each outdated
+    /// dependency whose sources Cargo has cached
+    #[arg(long)]
+    semver_checks: bool,
+
+    /// Compare the license of the current and latest version of each outdated dependency,
+    /// and check new licenses against `allowed-licenses` in `[workspace.metadata.stale]`
+    #[arg(long)]
+    licenses: bool,
+
+    /// Flag outdated dependencies whose latest version has a different publisher than the
+    /// current one, or whose crates.io owners changed since the last run
+    #[arg(long)]
+    publishers: bool,
+
+    /// Fail when any manifest produced a warning or error instead of checking what could be read
+    #[arg(long, global = true)]
+    strict: bool,
+
     /// Verbose output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Manage offline version snapshots
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// Compute the Rust version Cargo.lock actually needs and what pushes it past `rust-version`
+    Msrv,
+    /// List crates locked in several versions and the upgrades that would drop the old copies
+    Dupes,
+    /// Check how workspace members declare shared dependencies
+    Hygiene {
+        /// Rewrite the manifests: move shared requirements into `[workspace.dependencies]`
+        /// and switch members to `workspace = true`
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Show how a crate's own dependencies change between two of its versions
+    Diff {
+        /// Crate to compare
+        #[arg(value_name = "CRATE")]
+        crate_name: String,
+        /// Version (or requirement) to compare from [default: the locked version]
+        from: Option<String>,
+        /// Version (or requirement) to compare to [default: the latest release]
+        to: Option<String>,
+    },
+    /// Show a crate's changelog between two versions, from Cargo's cached sources
+    Explain {
+        /// Crate to explain
+        #[arg(value_name = "CRATE")]
+        crate_name: String,
+        /// Version (or requirement) to read from [default: the locked version]
+        from: Option<String>,
+        /// Version (or requirement) to read up to [default: the latest release]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotAction {
+    /// Write the version lists of every workspace dependency to a compressed file
+    Export {
+        /// Snapshot file to write
+        #[arg(short, long, default_value = "cargo-stale-snapshot.json.gz")]
+        output: String,
+
+        /// Also include every registry package from Cargo.lock
+        #[arg(long)]
+        lockfile: bool,
+    },
+}
+
 impl Cli {
     pub fn output_filter(&self) -> OutputFilter {
         if self.outdated_only {
@@ -117,7 +369,154 @@ impl Cli {
         }
     }
 
+    pub fn prerelease_policy(&self) -> PrereleasePolicy {
+        if self.pre {
+            PrereleasePolicy::Include
+        } else {
+            PrereleasePolicy::Exclude
+        }
+    }
+
+    pub fn diagnostics_policy(&self) -> DiagnosticsPolicy {
+        if self.strict {
+            DiagnosticsPolicy::Strict
+        } else {
+            DiagnosticsPolicy::Report
+        }
+    }
+
+    /// Advisory database to check against: the `--advisory-db` directory, or
+    /// cargo-audit's clone under `$CARGO_HOME` when the flag has no value.
+    pub fn advisory_db_path(&self) -> Option<PathBuf> {
+        self.advisory_db.as_ref().map(|dir| {
+            dir.as_ref().map_or_else(
+                || crate::utils::cargo_home().join("advisory-db"),
+                PathBuf::from,
+            )
+        })
+    }
+
+    pub fn advisory_policy(&self) -> AdvisoryPolicy {
+        if self.fail_on_advisory {
+            AdvisoryPolicy::Fail
+        } else {
+            AdvisoryPolicy::Report
+        }
+    }
+
+    pub fn yanked_policy(&self) -> YankedPolicy {
+        if self.fail_on_yanked {
+            YankedPolicy::Fail
+        } else {
+            YankedPolicy::Report
+        }
+    }
+
+    /// Whether a check covers every locked registry package rather than just the
+    /// declared dependencies, so their versions have to be looked up too:
+    /// `--fail-on-yanked` and advisories.
+    pub fn checks_locked_packages(&self) -> bool {
+        self.fail_on_yanked || self.advisory_db.is_some()
+    }
+
+    pub fn lookup_failure_policy(&self) -> LookupFailurePolicy {
+        if self.fail_on_lookup_error {
+            LookupFailurePolicy::Fail
+        } else {
+            LookupFailurePolicy::Report
+        }
+    }
+
+    pub fn transitive_preview(&self) -> TransitivePreview {
+        if self.transitive {
+            TransitivePreview::Shown
+        } else {
+            TransitivePreview::Hidden
+        }
+    }
+
+    pub fn semver_check_policy(&self) -> SemverCheckPolicy {
+        if self.semver_checks {
+            SemverCheckPolicy::Run
+        } else {
+            SemverCheckPolicy::Skipped
+        }
+    }
+
+    pub fn upgrade_explanation(&self) -> UpgradeExplanation {
+        if self.explain {
+            UpgradeExplanation::Shown
+        } else {
+            UpgradeExplanation::Hidden
+        }
+    }
+
+    /// Index to read full release entries (features, dependency lists) from: the
+    /// `--index-path` directory or the index of `--local-registry`. `None` means
+    /// the local crates.io index.
+    pub fn release_index_path(&self) -> Option<PathBuf> {
+        self.index_path().map(Path::to_path_buf).or_else(|| {
+            self.local_registry
+                .as_ref()
+                .map(|dir| Path::new(dir).join("index"))
+        })
+    }
+
+    pub fn license_check(&self) -> LicenseCheck {
+        if self.licenses {
+            LicenseCheck::Run
+        } else {
+            LicenseCheck::Skipped
+        }
+    }
+
+    pub fn publisher_check(&self) -> PublisherCheck {
+        if self.publishers {
+            PublisherCheck::Run
+        } else {
+            PublisherCheck::Skipped
+        }
+    }
+
+    /// Whether optional checks may query the crates.io API: snapshots, mirrors
+    /// and explicit index directories are meant to keep us offline.
+    pub fn allows_api_requests(&self) -> bool {
+        self.snapshot_path().is_none()
+            && self.vendor_source().is_none()
+            && self.index_path().is_none()
+    }
+
+    pub fn registry_api(&self) -> &str {
+        self.registry_api.trim_end_matches('/')
+    }
+
     pub fn use_online(&self) -> bool {
         self.online
     }
+
+    pub fn index_path(&self) -> Option<&Path> {
+        self.index_path.as_deref().map(Path::new)
+    }
+
+    pub fn snapshot_path(&self) -> Option<&Path> {
+        self.snapshot.as_deref().map(Path::new)
+    }
+
+    pub fn db_dump_path(&self) -> Option<&Path> {
+        self.db_dump.as_deref().map(Path::new)
+    }
+
+    pub fn vendor_source(&self) -> Option<VendorSource> {
+        if let Some(dir) = &self.vendor {
+            Some(VendorSource::Directory(PathBuf::from(dir)))
+        } else {
+            self.local_registry
+                .as_ref()
+                .map(|dir| VendorSource::LocalRegistry(PathBuf::from(dir)))
+        }
+    }
+
+    pub fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
 }
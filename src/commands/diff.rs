@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use crates_index::Version as Release;
+
+use crate::api::local_index::IndexReader;
+use crate::api::release_graph::{self, find_release};
+use crate::cargo::lockfile::{self, LockedPackage};
+use crate::cli::Cli;
+use crate::version::core::Version;
+
+/// `cargo stale diff <crate> [from] [to]`: how the crate's own dependency list and
+/// dependency tree change between two releases, read from the local index.
+pub fn run(cli: &Cli, crate_name: &str, from: Option<&str>, to: Option<&str>) -> Result<()> {
+    let reader = IndexReader::open(cli.release_index_path().as_deref())?;
+    let krate = reader
+        .read_crate(crate_name)
+        .map_err(|e| anyhow::anyhow!("Cannot read '{crate_name}' from the local index: {e}"))?;
+    let releases = krate.versions();
+
+    let (from, to) = release_graph::release_range(
+        crate_name,
+        releases,
+        (from, to),
+        cli.prerelease_policy().includes_prereleases(),
+        || current_release(cli, crate_name, releases),
+    )?;
+
+    let diff = release_graph::diff(&reader, crate_name, from, to);
+    crate::output::formatter::print_dependency_diff(&diff);
+    Ok(())
+}
+
+/// The release to compare from when none is given: the newest locked version of
+/// the crate, or failing that the newest release the manifests' requirement allows.
+pub fn current_release<'a>(
+    cli: &Cli,
+    crate_name: &str,
+    releases: &'a [Release],
+) -> Result<&'a Release> {
+    let locked = lockfile::find_lockfile(&cli.manifest)
+        .and_then(|_| lockfile::read_lockfile(&cli.manifest).ok())
+        .into_iter()
+        .flatten()
+        .filter(|p| p.name == crate_name && p.is_from_registry())
+        .filter_map(|p: LockedPackage| Version::parse(&p.version).map(|parsed| (parsed, p.version)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .and_then(|(_, version)| releases.iter().find(|r| r.version() == version));
+    if let Some(locked) = locked {
+        return Ok(locked);
+    }
+
+    let dependencies = crate::collect_dependencies(cli)?.dependencies;
+    dependencies
+        .iter()
+        .find(|(name, _, _, _)| name == crate_name)
+        .and_then(|(_, requirement, _, _)| find_release(releases, requirement))
+        .with_context(|| {
+            format!(
+                "{crate_name} is neither locked nor required here; give a version to compare from"
+            )
+        })
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use log::warn;
+
+use crate::api::local_index::IndexReader;
+use crate::cargo::{duplicates, lockfile};
+use crate::cli::Cli;
+
+/// `cargo stale dupes`: crates locked in several versions, and which upgrades
+/// would drop the old copies.
+pub fn run(cli: &Cli) -> Result<()> {
+    let packages = lockfile::read_lockfile(&cli.manifest)?;
+    // Holders' latest dependency lists come from the local index
+    let reader = if cli.use_online() {
+        None
+    } else {
+        IndexReader::open(cli.release_index_path().as_deref())
+            .inspect_err(|e| warn!("No local index to check upgrades against: {e}"))
+            .ok()
+    };
+
+    let duplicates = duplicates::find_duplicates(reader.as_ref(), &packages);
+    crate::output::formatter::print_duplicates(&duplicates);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+
+use crate::api::crate_source::CrateSource;
+use crate::api::local_index::IndexReader;
+use crate::api::release_graph;
+use crate::cli::Cli;
+use crate::commands::diff::current_release;
+use crate::types::ChangelogExcerpt;
+use crate::version::core::Version;
+
+/// Changelog files looked for at a package root, in order of preference.
+const CHANGELOG_FILES: [&str; 3] = ["CHANGELOG.md", "CHANGES.md", "RELEASES.md"];
+
+/// `cargo stale explain <crate> [from] [to]`: the changelog sections between two
+/// releases, read from the newer release's source in Cargo's registry cache.
+pub fn run(cli: &Cli, crate_name: &str, from: Option<&str>, to: Option<&str>) -> Result<()> {
+    let reader = IndexReader::open(cli.release_index_path().as_deref())?;
+    let krate = reader
+        .read_crate(crate_name)
+        .map_err(|e| anyhow::anyhow!("Cannot read '{crate_name}' from the local index: {e}"))?;
+    let releases = krate.versions();
+
+    let (from, to) = release_graph::release_range(
+        crate_name,
+        releases,
+        (from, to),
+        cli.prerelease_policy().includes_prereleases(),
+        || current_release(cli, crate_name, releases),
+    )?;
+
+    let source = CrateSource::find(crate_name, to.version()).with_context(|| {
+        format!(
+            "{crate_name} {} is not in Cargo's registry cache; fetch it first (e.g. `cargo fetch` in a project requiring it)",
+            to.version()
+        )
+    })?;
+    let Some((file, content)) = source.read_root_file(&CHANGELOG_FILES)? else {
+        anyhow::bail!(
+            "{crate_name} {} ships no {}",
+            to.version(),
+            CHANGELOG_FILES.join(", ")
+        );
+    };
+
+    let parse = |version: &str| {
+        Version::parse(version).with_context(|| format!("Cannot parse version {version}"))
+    };
+    let sections = crate::version::changelog::excerpt(
+        &content,
+        &parse(from.version())?,
+        &parse(to.version())?,
+    );
+    crate::output::formatter::print_changelog_excerpt(&ChangelogExcerpt {
+        name: crate_name.to_string(),
+        from: from.version().to_string(),
+        to: to.version().to_string(),
+        file,
+        source: source.path().display().to_string(),
+        sections,
+    });
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+
+use crate::cargo::{hygiene, parser};
+use crate::cli::{Cli, DependencyScope, WorkspaceMode};
+
+/// `cargo stale hygiene`: compare how the workspace members declare shared
+/// dependencies and, with `--fix`, move them to `[workspace.dependencies]`.
+pub fn run(cli: &Cli, fix: bool) -> Result<()> {
+    // Always the whole workspace, build-dependencies included: `--workspace`
+    // and `--include-build` only scope the outdated check
+    let crate::CollectedDependencies {
+        mut dependencies,
+        diagnostics,
+        manifests,
+        ..
+    } = crate::collect_dependencies_in(
+        cli,
+        DependencyScope::IncludeBuildDeps,
+        WorkspaceMode::IncludeMembers,
+    )?;
+    if manifests.len() < 2 {
+        anyhow::bail!("{} does not define a workspace with members", cli.manifest);
+    }
+    crate::output::formatter::print_diagnostics(&diagnostics);
+    if !diagnostics.is_empty() && cli.diagnostics_policy().is_strict() {
+        anyhow::bail!("{} manifest diagnostics (--strict)", diagnostics.len());
+    }
+
+    let mut sources: Vec<(&String, &String)> = manifests.iter().collect();
+    sources.sort();
+    for (source, path) in sources {
+        dependencies.extend(parser::target_dependencies(path, true, source));
+    }
+    let findings = hygiene::check(&dependencies);
+    crate::output::formatter::print_hygiene(&findings, fix);
+    if fix && !findings.is_empty() {
+        let outcome = hygiene::fix(&manifests["root"], &manifests, &findings)?;
+        crate::output::formatter::print_hygiene_fix(&outcome);
+    }
+    Ok(())
+}
@@ -0,0 +1,6 @@
+pub mod diff;
+pub mod dupes;
+pub mod explain;
+pub mod hygiene;
+pub mod msrv;
+pub mod snapshot;
@@ -0,0 +1,158 @@
+use anyhow::Result;
+
+use crate::cargo::lockfile::{self, LockedPackage};
+use crate::cli::Cli;
+use crate::types::{MsrvBlocker, MsrvReport, PublishedVersion};
+use crate::version::comparison::{compatible_range, needs_newer_rust};
+use crate::version::core::Version;
+
+/// `cargo stale msrv`: the Rust version the locked dependency graph needs, the
+/// packages pushing it past our `rust-version` and where to downgrade them to.
+pub async fn run(cli: &Cli) -> Result<()> {
+    let crate::CollectedDependencies {
+        dependencies: all_dependencies,
+        diagnostics,
+        rust_versions,
+        ..
+    } = crate::collect_dependencies(cli)?;
+    crate::output::formatter::print_diagnostics(&diagnostics);
+    if !diagnostics.is_empty() && cli.diagnostics_policy().is_strict() {
+        anyhow::bail!("{} manifest diagnostics (--strict)", diagnostics.len());
+    }
+
+    let locked: Vec<LockedPackage> = lockfile::read_lockfile(&cli.manifest)?
+        .into_iter()
+        .filter(LockedPackage::is_from_registry)
+        .collect();
+    let mut names: Vec<String> = locked.iter().map(|p| p.name.clone()).collect();
+    names.sort();
+    names.dedup();
+
+    let (lookup, _, _) = crate::resolve_versions(cli, &all_dependencies, &names).await?;
+    let declared = rust_versions.get("root").cloned();
+    let report = build_report(&locked, &lookup.versions, declared);
+
+    crate::output::formatter::print_msrv_report(&report);
+    Ok(())
+}
+
+fn build_report(
+    locked: &[LockedPackage],
+    versions: &std::collections::HashMap<String, Vec<PublishedVersion>>,
+    declared: Option<String>,
+) -> MsrvReport {
+    let declared_version = declared.as_deref().and_then(Version::parse);
+    let mut report = MsrvReport {
+        declared,
+        effective: None,
+        set_by: None,
+        blockers: Vec::new(),
+        unknown: 0,
+        locked: locked.len(),
+    };
+    let mut effective: Option<Version> = None;
+
+    for package in locked {
+        let published = versions.get(&package.name).map(Vec::as_slice);
+        let Some(release) = published
+            .into_iter()
+            .flatten()
+            .find(|v| v.num == package.version)
+        else {
+            report.unknown += 1;
+            continue;
+        };
+        let Some((rust_version, parsed)) = release
+            .rust_version
+            .as_deref()
+            .and_then(|r| Version::parse(r).map(|parsed| (r, parsed)))
+        else {
+            report.unknown += 1;
+            continue;
+        };
+
+        if effective.as_ref().is_none_or(|current| parsed > *current) {
+            effective = Some(parsed);
+            report.effective = Some(rust_version.to_string());
+            report.set_by = Some(format!("{} {}", package.name, package.version));
+        }
+        if let Some(declared) = &declared_version
+            && needs_newer_rust(release, declared)
+        {
+            report.blockers.push(MsrvBlocker {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                rust_version: rust_version.to_string(),
+                downgrade: published
+                    .and_then(|published| downgrade_for(published, &package.version, declared)),
+            });
+        }
+    }
+
+    report.blockers.sort_by(|a, b| {
+        Version::parse(&b.rust_version)
+            .cmp(&Version::parse(&a.rust_version))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    report
+}
+
+/// Newest non-yanked release below `locked`, and semver-compatible with it, that
+/// fits `msrv`. Releases declaring no `rust-version` are assumed to fit.
+fn downgrade_for(versions: &[PublishedVersion], locked: &str, msrv: &Version) -> Option<String> {
+    let locked = Version::parse(locked)?;
+    let compatible = compatible_range(&locked)?;
+    versions
+        .iter()
+        .filter(|v| !v.yanked && !needs_newer_rust(v, msrv))
+        .filter_map(|v| Version::parse(&v.num))
+        .filter(|v| v.pre_release.is_none() && *v < locked && compatible.matches(v))
+        .max()
+        .map(|v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(num: &str, rust_version: Option<&str>, yanked: bool) -> PublishedVersion {
+        PublishedVersion {
+            num: num.to_string(),
+            yanked,
+            rust_version: rust_version.map(str::to_string),
+            ..PublishedVersion::default()
+        }
+    }
+
+    #[test]
+    fn test_build_report() {
+        let versions = [(
+            "demo".to_string(),
+            vec![
+                release("1.0.0", Some("1.60"), false),
+                release("1.1.0", Some("1.70"), false),
+                release("1.1.1", Some("1.70"), true),
+                release("1.2.0", Some("1.85"), false),
+            ],
+        )]
+        .into_iter()
+        .collect();
+        let locked = |name: &str, version: &str| LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: None,
+            dependencies: Vec::new(),
+        };
+
+        let report = build_report(
+            &[locked("demo", "1.2.0"), locked("other", "0.1.0")],
+            &versions,
+            Some("1.75".to_string()),
+        );
+        assert_eq!(report.effective.as_deref(), Some("1.85"));
+        assert_eq!(report.set_by.as_deref(), Some("demo 1.2.0"));
+        assert_eq!(report.unknown, 1);
+        assert_eq!(report.blockers.len(), 1);
+        assert_eq!(report.blockers[0].downgrade.as_deref(), Some("1.1.0"));
+    }
+}
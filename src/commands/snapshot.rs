@@ -0,0 +1,50 @@
+use anyhow::Result;
+use log::warn;
+use std::{collections::BTreeMap, path::Path};
+
+use crate::api::{self, snapshot::Snapshot};
+use crate::cargo::lockfile::{self, LockedPackage};
+use crate::cli::Cli;
+
+/// `cargo stale snapshot export`: resolve the version list of every dependency
+/// (and optionally every locked registry package) and write it to `output`.
+pub async fn export(cli: &Cli, output: &str, include_lockfile: bool) -> Result<()> {
+    let crate::CollectedDependencies {
+        dependencies: all_dependencies,
+        diagnostics,
+        ..
+    } = crate::collect_dependencies(cli)?;
+    crate::output::formatter::print_diagnostics(&diagnostics);
+    if !diagnostics.is_empty() && cli.diagnostics_policy().is_strict() {
+        anyhow::bail!("{} manifest diagnostics (--strict)", diagnostics.len());
+    }
+    let mut names = crate::unique_crate_names(&all_dependencies);
+
+    if include_lockfile {
+        let locked = lockfile::read_lockfile(&cli.manifest)?;
+        for package in locked.into_iter().filter(LockedPackage::is_from_registry) {
+            if !names.contains(&package.name) {
+                names.push(package.name);
+            }
+        }
+    }
+
+    // The same lookup as a normal run, so the snapshot agrees with it on what
+    // each crate's versions are
+    let client = api::crates_io::create_client()?;
+    let lookup = crate::fetch_versions(&client, &names, cli).await?;
+    for (name, e) in &lookup.failures {
+        warn!("Crate '{name}' could not be resolved and is missing from the snapshot: {e}");
+    }
+    let crates: BTreeMap<_, _> = lookup.versions.into_iter().collect();
+
+    let snapshot = Snapshot::new(crates);
+    snapshot.write(Path::new(output))?;
+    println!(
+        "📸 Wrote snapshot of {} crates to {output} ({})",
+        snapshot.crates.len(),
+        snapshot.created_date()
+    );
+
+    Ok(())
+}
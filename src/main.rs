@@ -8,19 +8,22 @@
 
 use anyhow::Result;
 use clap::Parser;
-use std::{collections::HashMap, env, sync::Arc};
+use std::collections::{HashMap, hash_map::Entry};
+use std::{env, sync::Arc};
 use tokio::sync::Semaphore;
 
 mod api;
 mod cargo;
 mod cli;
+mod commands;
 mod output;
 mod types;
 mod utils;
 mod version;
 
+use cargo::diagnostics::Diagnostic;
 use cli::Cli;
-use types::Dependency;
+use types::{Dependency, LookupError, Status, VersionLookup};
 
 const MAX_CONCURRENT_REQUESTS: usize = 20;
 
@@ -30,23 +33,212 @@ async fn main() -> Result<()> {
     let cli = parse_cli();
     init_logging(&cli);
 
-    let all_dependencies = collect_dependencies(&cli)?;
-    let client = api::crates_io::create_client()?;
+    match cli.command() {
+        Some(cli::Command::Snapshot {
+            action: cli::SnapshotAction::Export { output, lockfile },
+        }) => return commands::snapshot::export(&cli, output, *lockfile).await,
+        Some(cli::Command::Msrv) => return commands::msrv::run(&cli).await,
+        Some(cli::Command::Dupes) => return commands::dupes::run(&cli),
+        Some(cli::Command::Hygiene { fix }) => return commands::hygiene::run(&cli, *fix),
+        Some(cli::Command::Diff {
+            crate_name,
+            from,
+            to,
+        }) => return commands::diff::run(&cli, crate_name, from.as_deref(), to.as_deref()),
+        Some(cli::Command::Explain {
+            crate_name,
+            from,
+            to,
+        }) => return commands::explain::run(&cli, crate_name, from.as_deref(), to.as_deref()),
+        None => {}
+    }
+
+    let CollectedDependencies {
+        dependencies: all_dependencies,
+        mut diagnostics,
+        rust_versions,
+        features,
+        manifests,
+    } = collect_dependencies(&cli)?;
+    let locked = read_locked_packages(&cli, &mut diagnostics);
+    let names = lookup_names(&cli, &all_dependencies, &locked);
 
     if cli.output_verbosity().is_verbose() {
         println!("📦 Found {} dependencies to check", all_dependencies.len());
     }
 
-    let version_cache = fetch_versions(&client, &all_dependencies, &cli).await?;
+    let (lookup, as_of, vendor_lag) = resolve_versions(&cli, &all_dependencies, &names).await?;
+
+    let metadata = match cli.db_dump_path() {
+        Some(path) => {
+            let dump = api::db_dump::DbDump::open(path, cli.output_verbosity().is_verbose())?;
+            dump.lookup(&unique_crate_names(&all_dependencies))
+        }
+        None => HashMap::new(),
+    };
+
+    let advisory_db = cli
+        .advisory_db_path()
+        .map(|path| api::advisory_db::AdvisoryDb::load(&path, &names))
+        .transpose()?;
 
-    let results = build_results(all_dependencies, &version_cache);
+    let yanked_locked = cargo::lockfile::find_yanked(&locked, &lookup.versions);
+    let vulnerable_locked = advisory_db.as_ref().map_or_else(Vec::new, |db| {
+        cargo::lockfile::find_vulnerable(&locked, &lookup.versions, db)
+    });
+    let feature_problems: Vec<types::FeatureProblem> = features
+        .iter()
+        .filter_map(|request| Some((request, lookup.versions.get(&request.name)?)))
+        .flat_map(|(request, versions)| version::features::check_features(request, versions))
+        .collect();
+    let lints = cargo::lints::check_requirements(&all_dependencies, &manifests, &lookup);
+    let manifest_conflicts = manifest_conflicts(&manifests, &all_dependencies, &lookup.versions);
+    let mut results = build_results(
+        all_dependencies,
+        &lookup,
+        &locked,
+        &metadata,
+        advisory_db.as_ref(),
+        &rust_versions,
+        cli.prerelease_policy(),
+    );
+
+    add_feature_changes(&mut results, &lookup, &locked, &features);
+    if cli.transitive_preview().is_shown() {
+        add_tree_changes(&cli, &mut results, &lookup, &locked);
+    }
+    if cli.semver_check_policy().is_run() {
+        add_semver_checks(&mut results, &lookup, &locked);
+    }
+    if cli.license_check().is_run() {
+        add_license_changes(&cli, &mut results, &lookup, &locked).await?;
+    }
+    if cli.publisher_check().is_run() {
+        add_publisher_changes(&cli, &mut results, &lookup, &locked).await?;
+    }
 
     if cli.output_verbosity().is_verbose() {
         println!("✅ Completed processing all dependencies");
     }
 
-    output::formatter::print_results(&results, &cli);
+    output::formatter::print_results(&results, &cli, as_of.as_deref());
+    if cli.upgrade_explanation().is_shown() {
+        output::formatter::print_upgrade_explanations(&results);
+    }
+    if let Some(lagging) = &vendor_lag {
+        output::formatter::print_vendor_lag(lagging);
+    }
+    output::formatter::print_feature_problems(&feature_problems);
+    output::formatter::print_manifest_conflicts(&manifest_conflicts);
+    output::formatter::print_links_conflicts(&links_conflicts(&cli, &results, &locked));
+
+    report_findings(
+        &cli,
+        &results,
+        &yanked_locked,
+        &vulnerable_locked,
+        &diagnostics,
+        &lints,
+    )
+}
+
+/// Packages each checked manifest declares with differing requirements across
+/// its own tables, target tables included.
+fn manifest_conflicts(
+    manifests: &HashMap<String, String>,
+    dependencies: &[types::DeclaredDependency],
+    versions: &HashMap<String, Vec<types::PublishedVersion>>,
+) -> Vec<types::ManifestConflict> {
+    let workspace_versions: HashMap<String, String> = dependencies
+        .iter()
+        .filter(|(_, _, dep_type, _)| *dep_type == types::DependencyType::Workspace)
+        .map(|(name, version, _, _)| (name.clone(), version.clone()))
+        .collect();
+    let mut sources: Vec<(&String, &String)> = manifests.iter().collect();
+    sources.sort();
+    sources
+        .into_iter()
+        .flat_map(|(source, path)| {
+            let requirements = cargo::parser::table_requirements(path, &workspace_versions);
+            cargo::consistency::find_conflicts(source, &requirements, versions)
+        })
+        .collect()
+}
+
+/// Print the sections that follow the report table and apply the `--fail-on-*`
+/// and `--strict` gates.
+fn report_findings(
+    cli: &Cli,
+    results: &[Dependency],
+    yanked_locked: &[types::YankedPackage],
+    vulnerable_locked: &[types::VulnerablePackage],
+    diagnostics: &[Diagnostic],
+    lints: &[cargo::lints::LintFinding],
+) -> Result<()> {
+    output::formatter::print_yanked_locked(yanked_locked);
+    output::formatter::print_vulnerable_locked(vulnerable_locked);
+    let vulnerabilities: std::collections::HashSet<(&str, &str)> = results
+        .iter()
+        .map(|dep| (dep.name.as_str(), &dep.advisories))
+        .chain(
+            vulnerable_locked
+                .iter()
+                .map(|package| (package.name.as_str(), &package.advisories)),
+        )
+        .flat_map(|(name, advisories)| {
+            advisories
+                .iter()
+                .filter(|advisory| advisory.severity.is_vulnerability())
+                .map(move |advisory| (name, advisory.id.as_str()))
+        })
+        .collect();
+    let yanked: std::collections::HashSet<(&str, &str)> = results
+        .iter()
+        .filter_map(|dep| match &dep.status {
+            Status::Yanked { version, .. } => Some((dep.name.as_str(), version.as_str())),
+            _ => None,
+        })
+        .chain(
+            yanked_locked
+                .iter()
+                .map(|package| (package.name.as_str(), package.version.as_str())),
+        )
+        .collect();
+
+    output::formatter::print_diagnostics(diagnostics);
+    output::formatter::print_lints(lints);
+    let failed = output::formatter::print_lookup_failures(results);
 
+    // Every section is printed before any of them decides the exit status
+    let mut failures = Vec::new();
+    if !diagnostics.is_empty() && cli.diagnostics_policy().is_strict() {
+        failures.push(format!(
+            "{} manifest diagnostics (--strict)",
+            diagnostics.len()
+        ));
+    }
+    let denied = lints.iter().filter(|lint| lint.level.is_deny()).count();
+    if denied > 0 {
+        failures.push(format!("{denied} denied requirement lints"));
+    }
+    if failed > 0 && cli.lookup_failure_policy().fails() {
+        failures.push(format!("{failed} dependency lookups failed"));
+    }
+    if !vulnerabilities.is_empty() && cli.advisory_policy().fails() {
+        failures.push(format!(
+            "{} vulnerability advisories affect dependencies or Cargo.lock",
+            vulnerabilities.len()
+        ));
+    }
+    if !yanked.is_empty() && cli.yanked_policy().fails() {
+        failures.push(format!(
+            "{} yanked versions in requirements or Cargo.lock",
+            yanked.len()
+        ));
+    }
+    if !failures.is_empty() {
+        anyhow::bail!("{}", failures.join("; "));
+    }
     Ok(())
 }
 
@@ -77,64 +269,232 @@ fn init_logging(cli: &Cli) {
     }
 }
 
-fn collect_dependencies(cli: &Cli) -> Result<Vec<(String, String, types::DependencyType, String)>> {
+/// Everything read from the manifests of the checked packages.
+struct CollectedDependencies {
+    dependencies: Vec<types::DeclaredDependency>,
+    diagnostics: Vec<Diagnostic>,
+    /// Declared `rust-version` per source package (`root` for the root manifest)
+    rust_versions: HashMap<String, String>,
+    features: Vec<types::FeatureRequest>,
+    /// Manifest path per source package (`root` for the root manifest)
+    manifests: HashMap<String, String>,
+}
+
+/// Dependencies of the root manifest and, in workspace mode, every member.
+/// A member that fails to load is reported in the diagnostics and skipped;
+/// only an unreadable root manifest is fatal.
+fn collect_dependencies(cli: &Cli) -> Result<CollectedDependencies> {
+    collect_dependencies_in(cli, cli.dependency_scope(), cli.workspace_mode())
+}
+
+/// [`collect_dependencies`] with an explicit scope, for commands that always
+/// look at the whole workspace whatever `--workspace` and `--include-build` say.
+fn collect_dependencies_in(
+    cli: &Cli,
+    scope: cli::DependencyScope,
+    mode: cli::WorkspaceMode,
+) -> Result<CollectedDependencies> {
     let mut all_deps = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut rust_versions = HashMap::new();
+    let mut manifests = HashMap::from([(
+        "root".to_string(),
+        utils::ensure_cargo_toml_path(&cli.manifest).to_string(),
+    )]);
+    let include_build = scope.includes_build_deps();
 
-    let main_deps = cargo::parser::parse_cargo_toml(
+    let main_deps =
+        cargo::parser::parse_cargo_toml(&cli.manifest, include_build, "root", &mut diagnostics)?;
+    all_deps.extend(main_deps);
+    let workspace_versions: HashMap<String, String> = all_deps
+        .iter()
+        .filter(|(_, _, dep_type, _)| *dep_type == types::DependencyType::Workspace)
+        .map(|(name, version, _, _)| (name.clone(), version.clone()))
+        .collect();
+    let mut features = cargo::parser::requested_features(
         &cli.manifest,
-        cli.dependency_scope().includes_build_deps(),
+        include_build,
         "root",
-    )?;
-    all_deps.extend(main_deps);
+        &workspace_versions,
+    );
 
-    if cli.workspace_mode().includes_members() {
-        let workspace_members = cargo::workspace::get_workspace_members(&cli.manifest)?;
+    if mode.includes_members() {
+        let workspace_members =
+            cargo::workspace::get_workspace_members(&cli.manifest, &mut diagnostics)?;
         for member_path in workspace_members {
             if cli.output_verbosity().is_verbose() {
                 println!("📦 Checking workspace member: {member_path}");
             }
-            let member_deps = cargo::parser::parse_cargo_toml(
+            let member_name = cargo::workspace::get_crate_name(&member_path);
+            match cargo::parser::parse_cargo_toml(
+                &member_path,
+                include_build,
+                &member_name,
+                &mut diagnostics,
+            ) {
+                Ok(member_deps) => all_deps.extend(member_deps),
+                Err(diagnostic) => diagnostics.push(diagnostic),
+            }
+            features.extend(cargo::parser::requested_features(
                 &member_path,
-                cli.dependency_scope().includes_build_deps(),
-                &cargo::workspace::get_crate_name(&member_path),
-            )?;
-            all_deps.extend(member_deps);
+                include_build,
+                &member_name,
+                &workspace_versions,
+            ));
+            if let Some(rust_version) = cargo::workspace::package_rust_version(&member_path) {
+                rust_versions.insert(member_name.clone(), rust_version);
+            }
+            manifests.insert(member_name, member_path);
         }
     }
 
-    Ok(all_deps)
+    // Workspace dependencies are declared by the root, but must build with the
+    // oldest toolchain any member supports
+    let root_rust_version = cargo::workspace::package_rust_version(&cli.manifest)
+        .or_else(|| cargo::workspace::workspace_rust_version(&cli.manifest))
+        .or_else(|| {
+            rust_versions
+                .values()
+                .filter_map(|v| version::core::Version::parse(v).map(|parsed| (parsed, v)))
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, v)| v.clone())
+        });
+    if let Some(rust_version) = root_rust_version {
+        rust_versions.insert("root".to_string(), rust_version);
+    }
+
+    Ok(CollectedDependencies {
+        dependencies: all_deps,
+        diagnostics,
+        rust_versions,
+        features,
+        manifests,
+    })
 }
 
-async fn fetch_versions(
-    client: &reqwest::Client,
-    all_dependencies: &[(String, String, types::DependencyType, String)],
+fn unique_crate_names(all_dependencies: &[types::DeclaredDependency]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    all_dependencies
+        .iter()
+        .map(|(name, _, _, _)| name.clone())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Versions of every crate in `names` from the selected source, plus the date they
+/// are current as of (snapshots only) and the requirements a vendored mirror lags.
+async fn resolve_versions(
     cli: &Cli,
-) -> Result<HashMap<String, Option<String>>> {
-    let unique_names: Vec<String> = {
-        let mut seen = std::collections::HashSet::new();
-        all_dependencies
-            .iter()
-            .map(|(name, _, _, _)| name.clone())
-            .filter(|name| seen.insert(name.clone()))
-            .collect()
+    all_dependencies: &[types::DeclaredDependency],
+    names: &[String],
+) -> Result<(VersionLookup, Option<String>, Option<Vec<types::VendorLag>>)> {
+    if let Some(path) = cli.snapshot_path() {
+        let snapshot = api::snapshot::Snapshot::load(path)?;
+        if cli.output_verbosity().is_verbose() {
+            println!(
+                "📸 Using snapshot {} ({} crates)",
+                path.display(),
+                snapshot.crates.len()
+            );
+        }
+        let as_of = snapshot.created_date();
+        Ok((snapshot.lookup(names), Some(as_of), None))
+    } else if let Some(source) = cli.vendor_source() {
+        let vendored = api::vendor::fetch_vendored_versions(names, &source)?;
+        if cli.output_verbosity().is_verbose() {
+            println!(
+                "📦 Vendored mirror {}: {}/{} crates available",
+                source.path().display(),
+                vendored.versions.len(),
+                names.len()
+            );
+        }
+        let lagging = api::vendor::find_lagging(all_dependencies, &vendored.versions);
+        Ok((vendored, None, Some(lagging)))
+    } else {
+        let client = api::crates_io::create_client()?;
+        Ok((fetch_versions(&client, names, cli).await?, None, None))
+    }
+}
+
+/// Registry packages from the Cargo.lock governing the manifest, if there is one.
+/// A lockfile that exists but cannot be read is reported as a diagnostic.
+fn read_locked_packages(
+    cli: &Cli,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<cargo::lockfile::LockedPackage> {
+    let Some(path) = cargo::lockfile::find_lockfile(&cli.manifest) else {
+        return Vec::new();
     };
+    match cargo::lockfile::read_lockfile(&cli.manifest) {
+        Ok(locked) => locked
+            .into_iter()
+            .filter(cargo::lockfile::LockedPackage::is_from_registry)
+            .collect(),
+        Err(e) => {
+            diagnostics.push(Diagnostic::warning(
+                &path.to_string_lossy(),
+                format!("{e:#}; locked versions are not checked"),
+            ));
+            Vec::new()
+        }
+    }
+}
+
+/// Every crate whose versions are needed: the declared dependencies, then, when
+/// a check covers the whole lock graph, any other locked registry package.
+fn lookup_names(
+    cli: &Cli,
+    all_dependencies: &[types::DeclaredDependency],
+    locked: &[cargo::lockfile::LockedPackage],
+) -> Vec<String> {
+    let mut names = unique_crate_names(all_dependencies);
+    if !cli.checks_locked_packages() {
+        return names;
+    }
+    let mut seen: std::collections::HashSet<String> = names.iter().cloned().collect();
+    names.extend(
+        locked
+            .iter()
+            .filter(|package| seen.insert(package.name.clone()))
+            .map(|package| package.name.clone()),
+    );
+    names
+}
 
+/// Fetch the published versions of every dependency: from the local index,
+/// falling back to the crates.io API. Every crate that could not be resolved
+/// is recorded with the reason.
+async fn fetch_versions(
+    client: &reqwest::Client,
+    unique_names: &[String],
+    cli: &Cli,
+) -> Result<VersionLookup> {
     if cli.output_verbosity().is_verbose() {
         println!("📦 Unique crates to check: {}", unique_names.len());
     }
 
     // Try local index first (unless --online is specified)
     if !cli.use_online() {
-        match api::local_index::fetch_versions_from_local_index(&unique_names) {
-            Ok(cache) => {
-                let found = cache.values().filter(|v| v.is_some()).count();
+        match api::local_index::fetch_version_lists_from_local_index(unique_names, cli.index_path())
+        {
+            Ok(lookup) => {
                 if cli.output_verbosity().is_verbose() {
-                    println!("📚 Local index: resolved {}/{} crates", found, unique_names.len());
+                    println!(
+                        "📚 Local index: resolved {}/{} crates",
+                        lookup.versions.len(),
+                        unique_names.len()
+                    );
                 }
-                if found > 0 {
-                    return Ok(cache);
+                // An explicit index directory is authoritative: never go online
+                if !lookup.versions.is_empty() || cli.index_path().is_some() {
+                    return Ok(VersionLookup {
+                        from_local_cache: cli.index_path().is_none(),
+                        ..lookup
+                    });
                 }
             }
+            Err(e) if cli.index_path().is_some() => return Err(e),
             Err(e) => {
                 if cli.output_verbosity().is_verbose() {
                     println!("⚠️  Local index unavailable: {e}, falling back to online mode");
@@ -148,30 +508,40 @@ async fn fetch_versions(
     // Fallback: online HTTP API with concurrency limiter
     let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
     let version_tasks: Vec<_> = unique_names
-        .into_iter()
+        .iter()
+        .cloned()
         .map(|name| {
             let client = client.clone();
             let sem = semaphore.clone();
             let verbose = cli.output_verbosity().is_verbose();
+            let api_url = cli.registry_api().to_string();
 
-            tokio::spawn(async move {
+            let task_name = name.clone();
+            let task = tokio::spawn(async move {
                 let _permit = sem.acquire().await.unwrap();
                 if verbose {
-                    println!("Fetching latest version for: {name}");
+                    println!("Fetching versions for: {task_name}");
                 }
-                let latest_version = api::crates_io::get_latest_version(&client, &name).await;
-                (name, latest_version)
-            })
+                api::crates_io::get_published_versions(&client, &api_url, &task_name).await
+            });
+            (name, task)
         })
         .collect();
 
-    let mut version_cache = HashMap::new();
-    for task in version_tasks {
+    let mut lookup = VersionLookup::default();
+    for (name, task) in version_tasks {
         match task.await {
-            Ok((name, version)) => {
-                version_cache.insert(name, version);
+            Ok(Ok(versions)) => {
+                lookup.versions.insert(name, versions);
+            }
+            Ok(Err(e)) => {
+                lookup.failures.insert(name, e);
+            }
+            Err(e) => {
+                lookup
+                    .failures
+                    .insert(name, LookupError::Task(e.to_string()));
             }
-            Err(e) => eprintln!("Version fetch task failed: {e}"),
         }
     }
 
@@ -179,21 +549,666 @@ async fn fetch_versions(
         println!("✅ Completed fetching all versions");
     }
 
-    Ok(version_cache)
+    Ok(lookup)
 }
 
 fn build_results(
-    all_dependencies: Vec<(String, String, types::DependencyType, String)>,
-    version_cache: &HashMap<String, Option<String>>,
+    all_dependencies: Vec<types::DeclaredDependency>,
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+    metadata: &HashMap<String, types::CrateInfo>,
+    advisory_db: Option<&api::advisory_db::AdvisoryDb>,
+    rust_versions: &HashMap<String, String>,
+    prerelease_policy: cli::PrereleasePolicy,
 ) -> Vec<Dependency> {
     all_dependencies
         .into_iter()
-        .map(|(name, current_version, dep_type, source)| Dependency {
-            latest_version: version_cache.get(&name).cloned().flatten(),
-            name,
-            current_version,
-            dep_type,
-            source,
+        .map(|(name, current_version, dep_type, source)| {
+            let versions = lookup.versions.get(&name);
+            let locked_package = locked_package(&name, &current_version, locked);
+            let mut latest_version = versions
+                .and_then(|versions| {
+                    version::comparison::latest_for_requirement(
+                        versions,
+                        &current_version,
+                        prerelease_policy,
+                    )
+                })
+                .map(str::to_string);
+            let status = match (lookup.failures.get(&name), versions, &latest_version) {
+                // crates.io's 404, or no source listing the crate at all
+                (Some(LookupError::NotFound), _, _) | (None, None, _) => Status::NotFound,
+                (Some(e), _, _) => Status::LookupFailed(e.clone()),
+                (None, Some(versions), Some(latest)) => locked_package
+                    .and_then(|package| yanked_status(package, versions))
+                    .unwrap_or_else(|| {
+                        version::comparison::classify(&current_version, latest, versions)
+                    }),
+                (None, Some(versions), None) => {
+                    let (status, prerelease) = without_latest_status(versions, &current_version);
+                    latest_version = prerelease;
+                    locked_package
+                        .and_then(|package| yanked_status(package, versions))
+                        .unwrap_or(status)
+                }
+            };
+            let advisories = advisory_db.map_or_else(Vec::new, |db| {
+                dependency_advisories(db, &name, &current_version, locked_package, versions)
+            });
+            let msrv = rust_versions
+                .get(&source)
+                .and_then(|rust_version| version::core::Version::parse(rust_version))
+                .zip(versions)
+                .map(|(rust_version, versions)| {
+                    msrv_check(
+                        versions,
+                        &current_version,
+                        latest_version.as_deref(),
+                        &rust_version,
+                        prerelease_policy,
+                    )
+                });
+            Dependency {
+                status,
+                advisories,
+                msrv,
+                tree_change: None,
+                feature_changes: None,
+                semver_check: None,
+                license_change: None,
+                publisher_change: None,
+                metadata: metadata
+                    .get(&name)
+                    .map(|info| info.metadata(latest_version.as_deref())),
+                latest_version,
+                name,
+                current_version,
+                dep_type,
+                source,
+            }
         })
         .collect()
 }
+
+/// Compare the feature tables of the current (locked, or newest allowed) and the
+/// latest version of each outdated dependency.
+fn add_feature_changes(
+    results: &mut [Dependency],
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+    features: &[types::FeatureRequest],
+) {
+    for dep in results.iter_mut().filter(|dep| dep.is_outdated()) {
+        let (Some(versions), Some(latest)) = (lookup.versions.get(&dep.name), &dep.latest_version)
+        else {
+            continue;
+        };
+        let find = |num: &str| versions.iter().find(|v| v.num == num);
+        let current = current_release(dep, versions, locked).and_then(find);
+        let (Some(current), Some(latest)) = (current, find(latest)) else {
+            continue;
+        };
+
+        let mut enabled: Vec<String> = features
+            .iter()
+            .filter(|request| {
+                request.name == dep.name && request.requirement == dep.current_version
+            })
+            .flat_map(|request| request.features.iter().cloned())
+            .collect();
+        enabled.sort();
+        enabled.dedup();
+        dep.feature_changes = version::features::feature_changes(current, latest, &enabled)
+            .filter(|changes| !changes.is_empty());
+    }
+}
+
+/// The release an outdated dependency upgrades from: the locked one, or the
+/// newest the requirement allows.
+fn current_release<'a>(
+    dep: &'a Dependency,
+    versions: &'a [types::PublishedVersion],
+    locked: &'a [cargo::lockfile::LockedPackage],
+) -> Option<&'a str> {
+    locked_package(&dep.name, &dep.current_version, locked)
+        .map(|package| package.version.as_str())
+        .or_else(|| version::comparison::newest_matching(versions, &dep.current_version))
+}
+
+/// With `--semver-checks`: run `cargo semver-checks` on each outdated
+/// dependency's upgrade, once per crate and version pair.
+fn add_semver_checks(
+    results: &mut [Dependency],
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+) {
+    if !api::semver_checks::is_installed() {
+        log::warn!("cargo semver-checks is not installed; skipping API checks");
+        return;
+    }
+    let mut checked: HashMap<(String, String, String), types::SemverCheck> = HashMap::new();
+    for dep in results.iter_mut().filter(|dep| dep.is_outdated()) {
+        let (Some(versions), Some(latest)) = (lookup.versions.get(&dep.name), &dep.latest_version)
+        else {
+            continue;
+        };
+        let Some(from) = current_release(dep, versions, locked).map(str::to_string) else {
+            continue;
+        };
+        let check = checked
+            .entry((dep.name.clone(), from.clone(), latest.clone()))
+            .or_insert_with(|| types::SemverCheck {
+                compatible: version::core::Version::parse(&from)
+                    .and_then(|v| version::comparison::compatible_range(&v))
+                    .is_some_and(|range| range.matches_str(latest)),
+                breaks: api::semver_checks::check(&dep.name, &from, latest),
+                from,
+            });
+        dep.semver_check = Some(check.clone());
+    }
+}
+
+/// With `--licenses`: compare the licenses of each outdated dependency's current
+/// and latest release, and check the latest against `allowed-licenses`.
+async fn add_license_changes(
+    cli: &Cli,
+    results: &mut [Dependency],
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+) -> Result<()> {
+    let allowed = cargo::licenses::allowed_licenses(&cli.manifest);
+    let client = cli
+        .allows_api_requests()
+        .then(api::crates_io::create_client)
+        .transpose()?;
+
+    let mut licenses: HashMap<(String, String), Option<String>> = HashMap::new();
+    for dep in results.iter_mut().filter(|dep| dep.is_outdated()) {
+        let (Some(versions), Some(latest)) = (lookup.versions.get(&dep.name), &dep.latest_version)
+        else {
+            continue;
+        };
+        let Some(from) = current_release(dep, versions, locked).map(str::to_string) else {
+            continue;
+        };
+        for release in [&from, latest] {
+            if let Entry::Vacant(entry) = licenses.entry((dep.name.clone(), release.clone())) {
+                let known = versions.iter().find(|v| &v.num == release);
+                let license =
+                    release_license(cli, client.as_ref(), &dep.name, release, known).await;
+                entry.insert(license);
+            }
+        }
+
+        let current = licenses[&(dep.name.clone(), from.clone())].clone();
+        let Some(latest) = licenses[&(dep.name.clone(), latest.clone())].clone() else {
+            continue;
+        };
+        let allowed = allowed
+            .as_deref()
+            .map(|allowed| cargo::licenses::is_allowed(&latest, allowed));
+        let changed = current.as_ref().is_some_and(|current| *current != latest);
+        if changed || allowed == Some(false) {
+            dep.license_change = Some(types::LicenseChange {
+                from,
+                current,
+                latest,
+                allowed,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// License of one release: from the version source when it records licenses,
+/// then the manifest in Cargo's registry cache, then the crates.io versions endpoint.
+async fn release_license(
+    cli: &Cli,
+    client: Option<&reqwest::Client>,
+    name: &str,
+    version: &str,
+    known: Option<&types::PublishedVersion>,
+) -> Option<String> {
+    if let Some(license) = known.and_then(|release| release.license.clone()) {
+        return Some(license);
+    }
+    match api::crate_source::CrateSource::find(name, version).map(|source| source.license()) {
+        Some(Ok(Some(license))) => return Some(license),
+        Some(Err(e)) => log::warn!("{name} {version}: {e:#}"),
+        _ => {}
+    }
+    let client = client?;
+    api::crates_io::get_version_license(client, cli.registry_api(), name, version)
+        .await
+        .inspect_err(|e| log::warn!("Could not fetch the license of {name} {version}: {e}"))
+        .ok()
+        .flatten()
+}
+
+/// With `--publishers`: compare who published each outdated dependency's current
+/// and latest release, and record its owners to compare with the next run.
+async fn add_publisher_changes(
+    cli: &Cli,
+    results: &mut [Dependency],
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+) -> Result<()> {
+    if !cli.allows_api_requests() {
+        log::warn!("Publisher checks need the crates.io API; skipping them");
+        return Ok(());
+    }
+    let client = api::crates_io::create_client()?;
+    let mut names: Vec<String> = results
+        .iter()
+        .filter(|dep| dep.is_outdated())
+        .map(|dep| dep.name.clone())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+    let tasks: Vec<_> = names
+        .into_iter()
+        .map(|name| {
+            let client = client.clone();
+            let sem = semaphore.clone();
+            let api_url = cli.registry_api().to_string();
+            let task_name = name.clone();
+            // An index does not record publishers; API version lists already carry them
+            let from_index = lookup.from_local_cache;
+            let task = tokio::spawn(async move {
+                let _permit = sem.acquire().await.unwrap();
+                let publishers = async {
+                    if from_index {
+                        api::crates_io::get_publishers(&client, &api_url, &task_name)
+                            .await
+                            .map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                };
+                tokio::join!(
+                    publishers,
+                    api::crates_io::get_owners(&client, &api_url, &task_name)
+                )
+            });
+            (name, task)
+        })
+        .collect();
+
+    let mut history = api::publishers::OwnerHistory::load();
+    let mut ownership = HashMap::new();
+    for (name, task) in tasks {
+        match task.await {
+            Ok((Ok(publishers), Ok(owners))) => {
+                let publishers = publishers.unwrap_or_else(|| {
+                    lookup
+                        .versions
+                        .get(&name)
+                        .map(|versions| api::crates_io::publishers(versions))
+                        .unwrap_or_default()
+                });
+                let owner_changes = history.update(&name, owners);
+                ownership.insert(name, (publishers, owner_changes));
+            }
+            Ok((Err(e), _) | (_, Err(e))) => {
+                log::warn!("Could not fetch the publishers of '{name}': {e}");
+            }
+            Err(e) => log::warn!("Publisher fetch task for '{name}' failed: {e}"),
+        }
+    }
+    if let Err(e) = history.save() {
+        log::warn!("Could not record crate owners: {e:#}");
+    }
+
+    for dep in results.iter_mut().filter(|dep| dep.is_outdated()) {
+        let (Some(versions), Some(latest)) = (lookup.versions.get(&dep.name), &dep.latest_version)
+        else {
+            continue;
+        };
+        let (Some(from), Some((publishers, (owners_added, owners_removed)))) = (
+            current_release(dep, versions, locked).map(str::to_string),
+            ownership.get(&dep.name),
+        ) else {
+            continue;
+        };
+        let change = types::PublisherChange {
+            current_publisher: publishers.get(&from).cloned().flatten(),
+            latest_publisher: publishers.get(latest).cloned().flatten(),
+            owners_added: owners_added.clone(),
+            owners_removed: owners_removed.clone(),
+            from,
+        };
+        if change.publisher_changed() || change.owners_changed() {
+            dep.publisher_change = Some(change);
+        }
+    }
+    Ok(())
+}
+
+/// Native `links` conflicts the upgrades of outdated dependencies would cause, and
+/// shared `links` packages that can only move in lockstep. Needs a local index;
+/// a snapshot does not record `links`.
+fn links_conflicts(
+    cli: &Cli,
+    results: &[Dependency],
+    locked: &[cargo::lockfile::LockedPackage],
+) -> Vec<types::LinksConflict> {
+    if cli.use_online() {
+        log::warn!("Links checks need a local index; skipping them with --online");
+        return Vec::new();
+    }
+    if cli.snapshot_path().is_some() {
+        log::warn!("Links checks are not covered by the snapshot; skipping them");
+        return Vec::new();
+    }
+    let mut upgrades: Vec<(String, String, String)> = results
+        .iter()
+        .filter(|dep| dep.is_outdated())
+        .filter_map(|dep| {
+            let package = locked_package(&dep.name, &dep.current_version, locked)?;
+            Some((
+                dep.name.clone(),
+                package.version.clone(),
+                dep.latest_version.clone()?,
+            ))
+        })
+        .collect();
+    upgrades.sort();
+    upgrades.dedup();
+
+    let reader = match api::local_index::IndexReader::open(cli.release_index_path().as_deref()) {
+        Ok(reader) => reader,
+        Err(e) => {
+            log::warn!("No local index to check native library links with: {e}");
+            return Vec::new();
+        }
+    };
+    let packages = match cargo::lockfile::read_lockfile(&cli.manifest) {
+        Ok(packages) => packages,
+        Err(e) => {
+            log::warn!("Skipping links checks: {e:#}");
+            return Vec::new();
+        }
+    };
+    cargo::links::find_links_conflicts(&reader, &packages, &upgrades)
+}
+
+/// Count the crates each outdated dependency's upgrade adds to its dependency tree,
+/// from the locked (or newest allowed) version to the latest. Needs a local index.
+fn add_tree_changes(
+    cli: &Cli,
+    results: &mut [Dependency],
+    lookup: &VersionLookup,
+    locked: &[cargo::lockfile::LockedPackage],
+) {
+    let reader = match api::local_index::IndexReader::open(cli.release_index_path().as_deref()) {
+        Ok(reader) => reader,
+        Err(e) => {
+            log::warn!("No local index to compare dependency trees with: {e}");
+            return;
+        }
+    };
+    for dep in results.iter_mut().filter(|dep| dep.is_outdated()) {
+        let (Some(versions), Some(latest)) = (lookup.versions.get(&dep.name), &dep.latest_version)
+        else {
+            continue;
+        };
+        let Some(from) = current_release(dep, versions, locked) else {
+            continue;
+        };
+        dep.tree_change = api::release_graph::tree_change(&reader, &dep.name, from, latest);
+    }
+}
+
+/// The newest release usable with our `rust-version`, and whether the latest needs more.
+fn msrv_check(
+    versions: &[types::PublishedVersion],
+    requirement: &str,
+    latest: Option<&str>,
+    rust_version: &version::core::Version,
+    prerelease_policy: cli::PrereleasePolicy,
+) -> types::MsrvCheck {
+    types::MsrvCheck {
+        latest_usable: version::comparison::latest_usable(
+            versions,
+            requirement,
+            rust_version,
+            prerelease_policy,
+        )
+        .map(str::to_string),
+        latest_requires: versions
+            .iter()
+            .find(|v| Some(v.num.as_str()) == latest)
+            .filter(|v| version::comparison::needs_newer_rust(v, rust_version))
+            .and_then(|v| v.rust_version.clone()),
+    }
+}
+
+/// The Cargo.lock entry resolving this requirement, if any.
+fn locked_package<'a>(
+    name: &str,
+    requirement: &str,
+    locked: &'a [cargo::lockfile::LockedPackage],
+) -> Option<&'a cargo::lockfile::LockedPackage> {
+    let req = version::requirement::VersionReq::parse(requirement)?;
+    locked
+        .iter()
+        .find(|package| package.name == name && req.matches_str(&package.version))
+}
+
+/// `Status::Yanked` when the locked version was yanked.
+fn yanked_status(
+    package: &cargo::lockfile::LockedPackage,
+    versions: &[types::PublishedVersion],
+) -> Option<Status> {
+    versions
+        .iter()
+        .any(|v| v.yanked && v.num == package.version)
+        .then(|| Status::Yanked {
+            version: package.version.clone(),
+            replacement: version::comparison::nearest_replacement(versions, &package.version)
+                .map(str::to_string),
+        })
+}
+
+/// Status of a crate whose version list holds nothing the pre-release policy
+/// lets count as its latest release, and the pre-release to report if that is why.
+fn without_latest_status(
+    versions: &[types::PublishedVersion],
+    requirement: &str,
+) -> (Status, Option<String>) {
+    if let Some(prerelease) = version::comparison::select_latest(versions, true) {
+        return (Status::PrereleaseAvailable, Some(prerelease.to_string()));
+    }
+    // Everything was yanked: name the newest release the requirement allows, if any
+    let requirement = version::requirement::VersionReq::parse(requirement);
+    let yanked = versions
+        .iter()
+        .filter(|v| v.yanked)
+        .filter_map(|v| version::core::Version::parse(&v.num).map(|parsed| (parsed, v)))
+        .max_by_key(|(parsed, _)| {
+            let allowed = requirement.as_ref().is_some_and(|req| req.matches(parsed));
+            (allowed, parsed.clone())
+        });
+    let status = match yanked {
+        Some((_, release)) => Status::Yanked {
+            version: release.num.clone(),
+            replacement: None,
+        },
+        None if versions.is_empty() => Status::LookupFailed(LookupError::NoUsableRelease(
+            "no releases listed".to_string(),
+        )),
+        None => Status::LookupFailed(LookupError::NoUsableRelease(
+            "no version number parses".to_string(),
+        )),
+    };
+    (status, None)
+}
+
+/// Advisories affecting the requirement's minimum version or the locked version,
+/// each advisory listed once.
+fn dependency_advisories(
+    db: &api::advisory_db::AdvisoryDb,
+    name: &str,
+    requirement: &str,
+    locked: Option<&cargo::lockfile::LockedPackage>,
+    versions: Option<&Vec<types::PublishedVersion>>,
+) -> Vec<types::AdvisoryMatch> {
+    let versions = versions.map(Vec::as_slice);
+    let minimum = version::requirement::VersionReq::parse(requirement)
+        .and_then(|req| req.lower_bound())
+        .map(|v| v.to_string());
+
+    let mut advisories = Vec::new();
+    for version in minimum.iter().chain(locked.map(|package| &package.version)) {
+        for advisory in db.check(name, version, versions) {
+            if !advisories
+                .iter()
+                .any(|known: &types::AdvisoryMatch| known.id == advisory.id)
+            {
+                advisories.push(advisory);
+            }
+        }
+    }
+    advisories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fail_on_lookup_error() {
+        let failed = Dependency {
+            name: "demo".to_string(),
+            current_version: "1.0".to_string(),
+            latest_version: None,
+            dep_type: types::DependencyType::Normal,
+            source: "root".to_string(),
+            status: Status::LookupFailed(LookupError::RateLimited),
+            metadata: None,
+            advisories: Vec::new(),
+            msrv: None,
+            tree_change: None,
+            feature_changes: None,
+            semver_check: None,
+            license_change: None,
+            publisher_change: None,
+        };
+        let diagnostics = [Diagnostic::warning("Cargo.toml", "dependency 'demo': odd")];
+        let report = |args: &[&str], diagnostics: &[Diagnostic]| {
+            let cli = Cli::parse_from(std::iter::once("cargo-stale").chain(args.iter().copied()));
+            report_findings(
+                &cli,
+                std::slice::from_ref(&failed),
+                &[],
+                &[],
+                diagnostics,
+                &[],
+            )
+        };
+
+        assert!(report(&[], &[]).is_ok());
+        let error = report(&["--fail-on-lookup-error"], &[]).unwrap_err();
+        assert_eq!(error.to_string(), "1 dependency lookups failed");
+        // A strict diagnostics failure no longer hides the lookup failure
+        let error = report(&["--fail-on-lookup-error", "--strict"], &diagnostics).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1 manifest diagnostics (--strict); 1 dependency lookups failed"
+        );
+    }
+
+    #[test]
+    fn test_results_without_latest() {
+        let release = |num: &str, yanked| types::PublishedVersion {
+            num: num.to_string(),
+            yanked,
+            ..types::PublishedVersion::default()
+        };
+        let lookup = VersionLookup {
+            versions: HashMap::from([
+                ("pre".to_string(), vec![release("0.1.0-alpha.1", false)]),
+                (
+                    "gone".to_string(),
+                    vec![release("1.0.0", true), release("2.0.0", true)],
+                ),
+                ("empty".to_string(), Vec::new()),
+            ]),
+            ..VersionLookup::default()
+        };
+        let declared = ["pre", "gone", "empty", "unknown"]
+            .map(|name| {
+                (
+                    name.to_string(),
+                    "1".to_string(),
+                    types::DependencyType::Normal,
+                    "root".to_string(),
+                )
+            })
+            .to_vec();
+        let results = build_results(
+            declared,
+            &lookup,
+            &[],
+            &HashMap::new(),
+            None,
+            &HashMap::new(),
+            cli::PrereleasePolicy::Exclude,
+        );
+
+        assert_eq!(results[0].status, Status::PrereleaseAvailable);
+        assert_eq!(results[0].latest_version.as_deref(), Some("0.1.0-alpha.1"));
+        assert_eq!(
+            results[1].status,
+            Status::Yanked {
+                version: "1.0.0".to_string(),
+                replacement: None,
+            }
+        );
+        assert_eq!(
+            results[2].status,
+            Status::LookupFailed(LookupError::NoUsableRelease(
+                "no releases listed".to_string()
+            ))
+        );
+        assert_eq!(results[3].status, Status::NotFound);
+    }
+
+    #[test]
+    fn test_links_lockstep_without_upgrades() {
+        let index = api::local_index::fixture::FixtureIndex::new("main-links");
+        index
+            .publish("sqlite-sys", "0.26.0", &[], Some("sqlite3"))
+            .publish("sqlite-sys", "0.28.0", &[], Some("sqlite3"));
+        let dir = api::local_index::fixture::TempDir::new("links");
+        let project = dir.path();
+        let registry = "source = \"registry+https://github.com/rust-lang/crates.io-index\"";
+        std::fs::write(
+            project.join("Cargo.lock"),
+            format!(
+                "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"db\", \"cache\"]\n\n\
+                 [[package]]\nname = \"db\"\nversion = \"0.29.0\"\n{registry}\ndependencies = [\"sqlite-sys\"]\n\n\
+                 [[package]]\nname = \"cache\"\nversion = \"1.0.0\"\n{registry}\ndependencies = [\"sqlite-sys\"]\n\n\
+                 [[package]]\nname = \"sqlite-sys\"\nversion = \"0.26.0\"\n{registry}\n"
+            ),
+        )
+        .unwrap();
+        let manifest = project.join("Cargo.toml");
+        let cli = Cli::parse_from([
+            "cargo-stale",
+            "--manifest",
+            manifest.to_str().unwrap(),
+            "--index-path",
+            index.path().to_str().unwrap(),
+        ]);
+
+        // Nothing is outdated, yet the shared `links` package is still reported
+        let conflicts = links_conflicts(&cli, &[], &[]);
+        assert!(matches!(
+            conflicts.as_slice(),
+            [types::LinksConflict::Lockstep { package, .. }] if package == "sqlite-sys 0.26.0"
+        ));
+    }
+}
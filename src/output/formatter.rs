@@ -1,17 +1,53 @@
+use crate::cargo::diagnostics::Diagnostic;
+use crate::cargo::lints::LintFinding;
 use crate::cli::Cli;
-use crate::types::Dependency;
+use crate::types::{
+    ChangelogExcerpt, ConflictResolution, Dependency, DependencyChange, DependencyDiff,
+    DuplicateCrate, FeatureChanges, FeatureProblem, HolderFix, HygieneFinding, LicenseChange,
+    LinksConflict, ManifestConflict, MsrvReport, PublisherChange, SemverCheck, Status, VendorLag,
+    VulnerablePackage, YankedPackage,
+};
 
-type DisplayData<'a> = (String, &'a str, &'a str, &'a str, &'static str);
+/// One table row: the cells of every visible column (Status excluded), the status
+/// and the notes shown after it (advisories, feature changes).
+struct DisplayRow<'a> {
+    cells: Vec<String>,
+    status: &'a Status,
+    notes: Vec<String>,
+}
+
+/// Optional columns, shown only when they carry information.
+#[derive(Debug, Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct Columns {
+    source: bool,
+    metadata: bool,
+    msrv: bool,
+    tree: bool,
+}
 
-#[derive(Debug)]
-struct ColumnWidths {
-    name: usize,
-    current: usize,
-    latest: usize,
-    source: usize,
+impl Columns {
+    fn headers(self) -> Vec<&'static str> {
+        let mut headers = vec!["Dependency", "Current Version", "Latest Version"];
+        if self.msrv {
+            headers.push("Latest Usable (MSRV)");
+        }
+        if self.tree {
+            headers.push("New Crates");
+        }
+        if self.source {
+            headers.push("Source");
+        }
+        if self.metadata {
+            headers.extend(["Released", "Downloads", "License"]);
+        }
+        headers
+    }
 }
 
-pub fn print_results(results: &[Dependency], cli: &Cli) {
+/// Print the report table. `as_of` names the date the latest versions were
+/// resolved at, when they come from a snapshot rather than a live index.
+pub fn print_results(results: &[Dependency], cli: &Cli, as_of: Option<&str>) {
     let filtered_results = filter_results(results, cli);
 
     if filtered_results.is_empty() {
@@ -19,21 +55,57 @@ pub fn print_results(results: &[Dependency], cli: &Cli) {
         return;
     }
 
-    let has_multiple_sources = check_multiple_sources(&filtered_results);
-    let display_data = prepare_display_data(&filtered_results);
-    let column_widths = calculate_column_widths(&display_data, has_multiple_sources);
+    let columns = Columns {
+        source: check_multiple_sources(&filtered_results),
+        metadata: filtered_results.iter().any(|dep| dep.metadata.is_some()),
+        msrv: filtered_results.iter().any(|dep| raises_msrv(dep)),
+        tree: filtered_results.iter().any(|dep| dep.tree_change.is_some()),
+    };
+    let display_data = prepare_display_data(&filtered_results, columns);
+    let column_widths = calculate_column_widths(&display_data, columns);
+
+    print_header(&column_widths, columns, as_of);
+    let outdated_count = print_dependency_rows(&display_data, &column_widths);
+    if columns.metadata && cli.output_verbosity().is_verbose() {
+        print_repositories(&filtered_results);
+    }
+    // Rows that are neither current nor outdated rule out an "all up to date" summary
+    let unsettled = filtered_results
+        .iter()
+        .filter(|dep| {
+            matches!(
+                dep.status,
+                Status::NotFound | Status::LookupFailed(_) | Status::Yanked { .. }
+            )
+        })
+        .count();
+    print_summary(outdated_count, unsettled, cli);
+
+    let raising = filtered_results
+        .iter()
+        .filter(|dep| raises_msrv(dep))
+        .count();
+    if raising > 0 {
+        println!("🦀 {raising} latest versions need a newer Rust than your rust-version");
+    }
+}
 
-    print_header(&column_widths, has_multiple_sources);
-    let outdated_count = print_dependency_rows(&display_data, &column_widths, has_multiple_sources);
-    print_summary(outdated_count, cli);
+/// Whether updating to the latest version would raise the package's MSRV.
+fn raises_msrv(dep: &Dependency) -> bool {
+    dep.msrv
+        .as_ref()
+        .is_some_and(|msrv| msrv.latest_requires.is_some())
 }
 
 fn filter_results<'a>(results: &'a [Dependency], cli: &Cli) -> Vec<&'a Dependency> {
-    if cli.output_filter().is_outdated_only() {
+    let mut filtered: Vec<&Dependency> = if cli.output_filter().is_outdated_only() {
         results.iter().filter(|dep| dep.is_outdated()).collect()
     } else {
         results.iter().collect()
-    }
+    };
+    // Outdated dependencies with advisories need attention first: updating fixes them
+    filtered.sort_by_key(|dep| !dep.is_outdated() || dep.advisories.is_empty());
+    filtered
 }
 
 fn print_empty_results_message(cli: &Cli) {
@@ -53,160 +125,738 @@ fn check_multiple_sources(filtered_results: &[&Dependency]) -> bool {
         > 1
 }
 
-fn prepare_display_data<'a>(filtered_results: &[&'a Dependency]) -> Vec<DisplayData<'a>> {
+fn prepare_display_data<'a>(
+    filtered_results: &[&'a Dependency],
+    columns: Columns,
+) -> Vec<DisplayRow<'a>> {
     filtered_results
         .iter()
         .map(|dep| {
-            let name_with_type = format!("{}{}", dep.name, dep.dep_type);
-            let latest_display = dep.latest_version.as_deref().unwrap_or("N/A");
-            let status = get_status_text(dep);
-
-            (
-                name_with_type,
-                dep.current_version.as_str(),
-                latest_display,
-                dep.source.as_str(),
-                status,
-            )
+            let latest = dep.latest_version.as_deref().unwrap_or("N/A");
+            let mut cells = vec![
+                format!("{}{}", dep.name, dep.dep_type),
+                dep.current_version.clone(),
+                match dep.msrv.as_ref().and_then(|m| m.latest_requires.as_deref()) {
+                    Some(rust) => format!("{latest} (rust {rust})"),
+                    None => latest.to_string(),
+                },
+            ];
+            if columns.msrv {
+                cells.push(
+                    dep.msrv
+                        .as_ref()
+                        .and_then(|m| m.latest_usable.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+            }
+            if columns.tree {
+                cells.push(dep.tree_change.map_or_else(
+                    || "-".to_string(),
+                    |(added, removed)| net_change(added, removed),
+                ));
+            }
+            if columns.source {
+                cells.push(dep.source.clone());
+            }
+            if columns.metadata {
+                let metadata = dep.metadata.as_ref();
+                cells.push(
+                    metadata
+                        .and_then(|m| m.released.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+                cells.push(
+                    metadata
+                        .and_then(|m| m.downloads)
+                        .map_or_else(|| "-".to_string(), format_downloads),
+                );
+                cells.push(
+                    metadata
+                        .and_then(|m| m.license.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+            }
+
+            let mut notes = Vec::new();
+            if !dep.advisories.is_empty() {
+                let advisories: Vec<String> =
+                    dep.advisories.iter().map(ToString::to_string).collect();
+                notes.push(format!("🛡️ {}", advisories.join(", ")));
+            }
+            if let Some(changes) = dep.feature_changes.as_ref().filter(|c| c.affects_build()) {
+                notes.push(format!("🧩 {}", feature_summary(changes)));
+            }
+            if let Some(check) = &dep.semver_check {
+                notes.push(semver_summary(check));
+            }
+            if let Some(change) = &dep.license_change {
+                notes.push(license_summary(change));
+            }
+            if let Some(change) = &dep.publisher_change {
+                notes.push(publisher_summary(change, dep.latest_version.as_deref()));
+            }
+
+            DisplayRow {
+                cells,
+                status: &dep.status,
+                notes,
+            }
         })
         .collect()
 }
 
-fn get_status_text(dep: &Dependency) -> &'static str {
-    match &dep.latest_version {
-        Some(latest) => {
-            if dep.is_outdated() {
-                if is_prerelease_version(latest) {
-                    "🟡 Outdated (Pre)"
-                } else {
-                    "🔴 Outdated"
-                }
-            } else if is_prerelease_version(latest) {
-                "🟢 Latest (Pre)"
-            } else {
-                "✅ Latest"
-            }
+fn calculate_column_widths(display_data: &[DisplayRow], columns: Columns) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns.headers().iter().map(|h| h.len()).collect();
+
+    for row in display_data {
+        for (width, cell) in widths.iter_mut().zip(&row.cells) {
+            *width = (*width).max(cell.len());
         }
-        None => "❓ Unknown",
     }
+
+    widths.iter().map(|w| w + 2).collect()
 }
 
-fn calculate_column_widths(
-    display_data: &[DisplayData<'_>],
-    has_multiple_sources: bool,
-) -> ColumnWidths {
-    let mut max_name_width = "Dependency".len();
-    let mut max_current_width = "Current Version".len();
-    let mut max_latest_width = "Latest Version".len();
-    let mut max_source_width = if has_multiple_sources {
-        "Source".len()
-    } else {
-        0
-    };
+fn print_header(widths: &[usize], columns: Columns, as_of: Option<&str>) {
+    match as_of {
+        Some(date) => println!("\n📊 Dependency Check Results (latest as of {date}):"),
+        None => println!("\n📊 Dependency Check Results:"),
+    }
 
-    for (name, current, latest, source, _status) in display_data {
-        max_name_width = max_name_width.max(name.len());
-        max_current_width = max_current_width.max(current.len());
-        max_latest_width = max_latest_width.max(latest.len());
-        if has_multiple_sources {
-            max_source_width = max_source_width.max(source.len());
+    let headers: Vec<String> = columns.headers().iter().map(ToString::to_string).collect();
+    println!("{} Status", pad_cells(&headers, widths));
+}
+
+fn print_dependency_rows(display_data: &[DisplayRow], widths: &[usize]) -> usize {
+    let mut outdated_count = 0;
+
+    for row in display_data {
+        if row.status.is_outdated() {
+            outdated_count += 1;
         }
-    }
 
-    ColumnWidths {
-        name: max_name_width + 2,
-        current: max_current_width + 2,
-        latest: max_latest_width + 2,
-        source: if has_multiple_sources {
-            max_source_width + 2
+        if row.notes.is_empty() {
+            println!("{} {}", pad_cells(&row.cells, widths), row.status);
         } else {
-            0
-        },
+            println!(
+                "{} {}  {}",
+                pad_cells(&row.cells, widths),
+                row.status,
+                row.notes.join("  ")
+            );
+        }
     }
+
+    outdated_count
 }
 
-fn print_header(widths: &ColumnWidths, has_multiple_sources: bool) {
-    println!("\n📊 Dependency Check Results:");
+fn print_repositories(filtered_results: &[&Dependency]) {
+    let mut seen = std::collections::HashSet::new();
+    for dep in filtered_results {
+        if let Some(repository) = dep.metadata.as_ref().and_then(|m| m.repository.as_ref())
+            && seen.insert(&dep.name)
+        {
+            println!("🔗 {}: {repository}", dep.name);
+        }
+    }
+}
 
-    if has_multiple_sources {
-        println!(
-            "{:<name_width$} {:<current_width$} {:<latest_width$} {:<source_width$} Status",
-            "Dependency",
-            "Current Version",
-            "Latest Version",
-            "Source",
-            name_width = widths.name,
-            current_width = widths.current,
-            latest_width = widths.latest,
-            source_width = widths.source,
-        );
-    } else {
+fn pad_cells(cells: &[String], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{cell:<width$}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Abbreviate a download count: 950, 12.3K, 4.5M
+#[allow(clippy::cast_precision_loss)]
+fn format_downloads(downloads: u64) -> String {
+    match downloads {
+        0..1_000 => downloads.to_string(),
+        1_000..1_000_000 => format!("{:.1}K", downloads as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1}M", downloads as f64 / 1e6),
+        _ => format!("{:.1}B", downloads as f64 / 1e9),
+    }
+}
+
+fn print_summary(outdated_count: usize, unsettled: usize, cli: &Cli) {
+    if outdated_count > 0 {
+        println!("⚠️  Found {outdated_count} outdated dependencies");
+        if cli.output_verbosity().is_verbose() {
+            println!("💡 Use 'cargo update <crate_name>' to update specific dependencies");
+        }
+    } else if unsettled == 0 && !cli.output_filter().is_outdated_only() {
+        println!("🎉 All dependencies are up to date!");
+    }
+}
+
+/// Report Cargo.lock entries locked to a yanked version.
+pub fn print_yanked_locked(yanked: &[YankedPackage]) {
+    if yanked.is_empty() {
+        return;
+    }
+
+    println!("\n⛔ Yanked versions in Cargo.lock:");
+    for package in yanked {
+        match &package.replacement {
+            Some(replacement) => println!(
+                "  {} {} → cargo update -p {}@{} --precise {replacement}",
+                package.name, package.version, package.name, package.version
+            ),
+            None => println!(
+                "  {} {} (no compatible replacement)",
+                package.name, package.version
+            ),
+        }
+    }
+}
+
+/// Report Cargo.lock entries whose locked version has advisories.
+pub fn print_vulnerable_locked(vulnerable: &[VulnerablePackage]) {
+    if vulnerable.is_empty() {
+        return;
+    }
+
+    println!("\n🛡️  Advisories for versions in Cargo.lock:");
+    for package in vulnerable {
+        for advisory in &package.advisories {
+            println!("  {} {}: {advisory}", package.name, package.version);
+        }
+    }
+}
+
+/// Print the `cargo stale msrv` report.
+pub fn print_msrv_report(report: &MsrvReport) {
+    match (&report.effective, &report.set_by) {
+        (Some(effective), Some(set_by)) => {
+            println!("🦀 Effective minimum Rust version: {effective} (set by {set_by})");
+        }
+        _ => println!("🦀 No locked package declares a rust-version"),
+    }
+    match &report.declared {
+        Some(declared) => println!("   Declared rust-version: {declared}"),
+        None => {
+            println!("   No rust-version declared to check the locked packages against");
+        }
+    }
+    if report.unknown > 0 {
         println!(
-            "{:<name_width$} {:<current_width$} {:<latest_width$} Status",
-            "Dependency",
-            "Current Version",
-            "Latest Version",
-            name_width = widths.name,
-            current_width = widths.current,
-            latest_width = widths.latest,
+            "   {}/{} locked packages declare no rust-version or could not be looked up",
+            report.unknown, report.locked
         );
     }
+
+    if report.blockers.is_empty() {
+        if report.declared.is_some() {
+            println!("\n✅ Every locked package builds with the declared rust-version");
+        }
+        return;
+    }
+
+    println!("\n⬆️  Locked packages requiring a newer Rust:");
+    for blocker in &report.blockers {
+        match &blocker.downgrade {
+            Some(downgrade) => println!(
+                "  {} {} (rust {}) → cargo update -p {}@{} --precise {downgrade}",
+                blocker.name, blocker.version, blocker.rust_version, blocker.name, blocker.version
+            ),
+            None => println!(
+                "  {} {} (rust {}): no compatible older release fits",
+                blocker.name, blocker.version, blocker.rust_version
+            ),
+        }
+    }
 }
 
-fn print_dependency_rows(
-    display_data: &[DisplayData<'_>],
-    widths: &ColumnWidths,
-    has_multiple_sources: bool,
-) -> usize {
-    let mut outdated_count = 0;
+/// What `cargo semver-checks` found, calling out the surprises: a semver-compatible
+/// upgrade that breaks API, and a breaking version jump that does not.
+fn semver_summary(check: &SemverCheck) -> String {
+    let breaks = match &check.breaks {
+        Ok(breaks) => breaks,
+        Err(reason) => return format!("🔍 semver-checks failed: {reason}"),
+    };
+    let total: usize = breaks.iter().map(|(_, count)| count).sum();
+    let kinds: Vec<String> = breaks
+        .iter()
+        .map(|(lint, count)| match count {
+            1 => lint.clone(),
+            _ => format!("{lint} ×{count}"),
+        })
+        .collect();
+    match (total, check.compatible) {
+        (0, true) => "✅ no API breaks".to_string(),
+        (0, false) => "✅ no API breaks despite the major bump".to_string(),
+        (_, true) => format!(
+            "💥 {total} API breaks in a compatible bump: {}",
+            kinds.join(", ")
+        ),
+        (_, false) => format!("🔍 {total} API breaks: {}", kinds.join(", ")),
+    }
+}
 
-    for (name, current, latest, source, status) in display_data {
-        if status.contains("Outdated") {
-            outdated_count += 1;
+/// A license change, e.g. `⚖️ MIT → GPL-3.0 (not allowed)`.
+fn license_summary(change: &LicenseChange) -> String {
+    let verdict = match change.allowed {
+        Some(false) => " (not allowed)",
+        _ => "",
+    };
+    match &change.current {
+        Some(current) if *current != change.latest => {
+            format!("⚖️ {current} → {}{verdict}", change.latest)
         }
+        _ => format!("⚖️ {}{verdict}", change.latest),
+    }
+}
+
+/// A publisher or owner change, e.g.
+/// `👤 2.0.0 published by mallory, 1.4.0 by alice; owners +mallory -alice since last run`.
+fn publisher_summary(change: &PublisherChange, latest: Option<&str>) -> String {
+    let mut parts = Vec::new();
+    if let (Some(current), Some(publisher)) = (&change.current_publisher, &change.latest_publisher)
+        && change.publisher_changed()
+    {
+        parts.push(format!(
+            "{} published by {publisher}, {} by {current}",
+            latest.unwrap_or("latest"),
+            change.from
+        ));
+    }
+    if change.owners_changed() {
+        let owners: Vec<String> = change
+            .owners_added
+            .iter()
+            .map(|owner| format!("+{owner}"))
+            .chain(
+                change
+                    .owners_removed
+                    .iter()
+                    .map(|owner| format!("-{owner}")),
+            )
+            .collect();
+        parts.push(format!("owners {} since last run", owners.join(" ")));
+    }
+    format!("👤 {}", parts.join("; "))
+}
 
-        if has_multiple_sources {
+/// The feature changes that affect our build, e.g. `rc removed, default +alloc`.
+fn feature_summary(changes: &FeatureChanges) -> String {
+    let mut parts = Vec::new();
+    if !changes.enabled_missing.is_empty() {
+        parts.push(format!("{} removed", changes.enabled_missing.join(", ")));
+    }
+    let mut default_changes: Vec<String> = changes
+        .default_added
+        .iter()
+        .map(|f| format!("+{f}"))
+        .collect();
+    default_changes.extend(changes.default_removed.iter().map(|f| format!("-{f}")));
+    if !default_changes.is_empty() {
+        parts.push(format!("default {}", default_changes.join(" ")));
+    }
+    parts.join(", ")
+}
+
+/// With `--explain`: the feature table changes of every outdated dependency's upgrade.
+pub fn print_upgrade_explanations(results: &[Dependency]) {
+    let mut seen = std::collections::HashSet::new();
+    let explained: Vec<(&Dependency, &FeatureChanges)> = results
+        .iter()
+        .filter_map(|dep| Some((dep, dep.feature_changes.as_ref()?)))
+        .filter(|(dep, changes)| seen.insert((&dep.name, &changes.from)))
+        .collect();
+    if explained.is_empty() {
+        return;
+    }
+
+    println!("\n📖 Feature changes:");
+    for (dep, changes) in explained {
+        println!(
+            "  {} {} → {}",
+            dep.name,
+            changes.from,
+            dep.latest_version.as_deref().unwrap_or("?")
+        );
+        let list = |features: &[String]| features.join(", ");
+        if !changes.enabled_missing.is_empty() {
             println!(
-                "{name:<name_width$} {current:<current_width$} {latest:<latest_width$} {source:<source_width$} {status}",
-                name_width = widths.name,
-                current_width = widths.current,
-                latest_width = widths.latest,
-                source_width = widths.source,
+                "    ⚠️  enabled here but gone: {}",
+                list(&changes.enabled_missing)
             );
-        } else {
+        }
+        if !changes.default_added.is_empty() {
+            println!("    default now enables: {}", list(&changes.default_added));
+        }
+        if !changes.default_removed.is_empty() {
             println!(
-                "{name:<name_width$} {current:<current_width$} {latest:<latest_width$} {status}",
-                name_width = widths.name,
-                current_width = widths.current,
-                latest_width = widths.latest,
+                "    default no longer enables: {}",
+                list(&changes.default_removed)
             );
         }
+        if !changes.removed.is_empty() {
+            println!("    removed: {}", list(&changes.removed));
+        }
+        if !changes.added.is_empty() {
+            println!("    added: {}", list(&changes.added));
+        }
     }
+}
 
-    outdated_count
+/// Signed difference between crates added and removed, e.g. `+3` or `-1`.
+fn net_change(added: usize, removed: usize) -> String {
+    if added >= removed {
+        format!("+{}", added - removed)
+    } else {
+        format!("-{}", removed - added)
+    }
 }
 
-fn print_summary(outdated_count: usize, cli: &Cli) {
-    if outdated_count > 0 {
-        println!("⚠️  Found {outdated_count} outdated dependencies");
-        if cli.output_verbosity().is_verbose() {
-            println!("💡 Use 'cargo update <crate_name>' to update specific dependencies");
+/// Print how a crate's dependency list and dependency tree change between two releases.
+pub fn print_dependency_diff(diff: &DependencyDiff) {
+    println!("\n🔀 {} {} → {}", diff.name, diff.from, diff.to);
+    if diff.changes.is_empty() {
+        println!("  No changes to the dependency list");
+    }
+    let optional = |optional: bool| if optional { " (optional)" } else { "" };
+    for change in &diff.changes {
+        match change {
+            DependencyChange::Added(dep) => println!(
+                "  + {}{} {}{}",
+                dep.name,
+                dep.kind,
+                dep.requirement,
+                optional(dep.optional)
+            ),
+            DependencyChange::Removed(dep) => println!(
+                "  - {}{} {}{}",
+                dep.name,
+                dep.kind,
+                dep.requirement,
+                optional(dep.optional)
+            ),
+            DependencyChange::Requirement { dependency, from } => println!(
+                "  ~ {}{} {from} → {}",
+                dependency.name, dependency.kind, dependency.requirement
+            ),
+            DependencyChange::BecameOptional(dep) => {
+                println!("  ? {}{} is now optional", dep.name, dep.kind);
+            }
+            DependencyChange::BecameRequired(dep) => {
+                println!("  ! {}{} is now required", dep.name, dep.kind);
+            }
         }
-    } else if !cli.output_filter().is_outdated_only() {
-        println!("🎉 All dependencies are up to date!");
+    }
+
+    println!(
+        "\n🌳 Dependency tree: {} crates (+{} new, -{} gone)",
+        net_change(diff.added_crates.len(), diff.removed_crates.len()),
+        diff.added_crates.len(),
+        diff.removed_crates.len()
+    );
+    if !diff.added_crates.is_empty() {
+        println!("  new: {}", diff.added_crates.join(", "));
+    }
+    if !diff.removed_crates.is_empty() {
+        println!("  gone: {}", diff.removed_crates.join(", "));
+    }
+}
+
+/// Report packages that would link the same native library twice.
+pub fn print_links_conflicts(conflicts: &[LinksConflict]) {
+    if conflicts.is_empty() {
+        return;
+    }
+
+    println!("\n🔗 Native library (links) conflicts:");
+    for conflict in conflicts {
+        match conflict {
+            LinksConflict::Upgrade {
+                dependency,
+                version,
+                links,
+                incoming,
+                existing,
+            } => println!(
+                "  {dependency} {version} would bring in {incoming}, but {existing} already links \"{links}\""
+            ),
+            LinksConflict::Lockstep {
+                links,
+                package,
+                latest,
+                dependents,
+            } => println!(
+                "  {package} (links \"{links}\") is shared by {}: they can only move to {latest} together",
+                dependents.join(", ")
+            ),
+        }
+    }
+}
+
+/// Report packages a manifest declares with differing requirements, what Cargo
+/// resolves them to and the requirement to unify on.
+pub fn print_manifest_conflicts(conflicts: &[ManifestConflict]) {
+    if conflicts.is_empty() {
+        return;
+    }
+
+    println!("\n🔀 Conflicting requirements within a manifest:");
+    for conflict in conflicts {
+        println!("  {}: {}", conflict.source, conflict.package);
+        for (table, requirement) in &conflict.declarations {
+            println!("    [{table}] {requirement}");
+        }
+        match &conflict.resolution {
+            ConflictResolution::Unified {
+                requirement,
+                version,
+            } => {
+                let resolved = version
+                    .as_ref()
+                    .map_or_else(String::new, |v| format!(" ({v})"));
+                println!(
+                    "    → Cargo resolves one version for every table{resolved}, so \"{requirement}\" wins; unify on it"
+                );
+            }
+            ConflictResolution::Incompatible { suggested } => {
+                println!(
+                    "    ❌ no single version satisfies every entry; unify on \"{suggested}\""
+                );
+            }
+        }
+    }
+}
+
+/// Print the changelog sections `cargo stale explain` extracted.
+pub fn print_changelog_excerpt(excerpt: &ChangelogExcerpt) {
+    println!(
+        "\n📜 {} {} → {} ({} from {})",
+        excerpt.name, excerpt.from, excerpt.to, excerpt.file, excerpt.source
+    );
+    if excerpt.sections.is_empty() {
+        println!(
+            "  No section of {} names a version in this range",
+            excerpt.file
+        );
+        return;
+    }
+
+    for section in &excerpt.sections {
+        println!("\n## {}", section.heading);
+        if !section.body.is_empty() {
+            println!("{}", section.body);
+        }
+    }
+}
+
+/// Print the `cargo stale dupes` report.
+pub fn print_duplicates(duplicates: &[DuplicateCrate]) {
+    if duplicates.is_empty() {
+        println!("🎉 No crate is locked in more than one version");
+        return;
+    }
+
+    println!("\n📚 Crates locked in several versions:");
+    for duplicate in duplicates {
+        let older: Vec<&str> = duplicate
+            .copies
+            .iter()
+            .map(|c| c.version.as_str())
+            .collect();
+        println!(
+            "  {} {} (also {})",
+            duplicate.name,
+            duplicate.newest,
+            older.join(", ")
+        );
+        for copy in &duplicate.copies {
+            if copy.is_removable() {
+                println!("    {} ✅ goes away by upgrading:", copy.version);
+            } else {
+                println!("    {} held by:", copy.version);
+            }
+            for holder in &copy.holders {
+                let fix = match &holder.fix {
+                    HolderFix::Upgrade(latest) => format!("→ {latest}"),
+                    HolderFix::Member => "workspace member: raise its requirement".to_string(),
+                    HolderFix::Stuck {
+                        latest,
+                        requirement,
+                    } => format!("❌ latest {latest} still requires {requirement}"),
+                    HolderFix::Unknown => "? not in the local index".to_string(),
+                };
+                println!("      {} {} {fix}", holder.name, holder.version);
+            }
+        }
+    }
+
+    let copies = duplicates.iter().flat_map(|d| &d.copies);
+    let removable = copies.clone().filter(|c| c.is_removable()).count();
+    println!(
+        "\n✂️  {removable} of {} old copies go away by upgrading their holders",
+        copies.count()
+    );
+}
+
+/// Report inconsistent dependency declarations across workspace members.
+pub fn print_hygiene(findings: &[HygieneFinding], fixing: bool) {
+    if findings.is_empty() {
+        println!("🎉 Workspace members declare their shared dependencies consistently");
+        return;
+    }
+
+    println!("\n🧹 Workspace dependency hygiene:");
+    for finding in findings {
+        match finding {
+            HygieneFinding::Mismatch { name, requirements } => {
+                let requirements: Vec<String> = requirements
+                    .iter()
+                    .map(|(source, requirement)| format!("{source} {requirement}"))
+                    .collect();
+                println!(
+                    "  ⚠️  {name}: mismatched requirements: {}",
+                    requirements.join(", ")
+                );
+            }
+            HygieneFinding::MoveToWorkspace {
+                name,
+                requirement,
+                members,
+            } => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|(member, dep_type, requirement)| {
+                        format!("{member}{dep_type} {requirement}")
+                    })
+                    .collect();
+                println!(
+                    "  📥 {name}: move to [workspace.dependencies] as \"{requirement}\" (declared by {})",
+                    members.join(", ")
+                );
+            }
+            HygieneFinding::UseWorkspace {
+                name,
+                member: (member, dep_type, requirement),
+                workspace_requirement,
+            } => println!(
+                "  🔗 {name}: {member}{dep_type} declares {requirement}, use `workspace = true` ({workspace_requirement})"
+            ),
+        }
+    }
+    if !fixing {
+        println!("\n💡 Run with --fix to rewrite the manifests");
+    }
+}
+
+/// Summarize what `hygiene --fix` rewrote and what it had to leave alone.
+pub fn print_hygiene_fix(outcome: &crate::cargo::hygiene::FixOutcome) {
+    println!(
+        "\n✍️  Switched {} entries to `workspace = true` across {} manifests",
+        outcome.rewritten, outcome.manifests
+    );
+    for skipped in &outcome.skipped {
+        println!("  ⏭️  left alone: {skipped}");
+    }
+}
+
+/// Report requested features missing from the lowest allowed version.
+pub fn print_feature_problems(problems: &[FeatureProblem]) {
+    if problems.is_empty() {
+        return;
+    }
+
+    println!("\n🧩 Feature checks:");
+    for problem in problems {
+        match problem {
+            FeatureProblem::MissingAtLowerBound {
+                name,
+                source,
+                features,
+                lowest,
+                tightened,
+            } => {
+                let features = features.join(", ");
+                match tightened {
+                    Some(tightened) => println!(
+                        "  {name} ({source}): {lowest} lacks {features}; require {name} = \"{tightened}\""
+                    ),
+                    None => println!(
+                        "  {name} ({source}): {lowest} lacks {features}, and no allowed version offers them"
+                    ),
+                }
+            }
+        }
+    }
+}
+
+/// List the problems found while loading manifests, if any.
+pub fn print_diagnostics(diagnostics: &[Diagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+
+    println!("\n🩺 Manifest diagnostics:");
+    for diagnostic in diagnostics {
+        println!("  {diagnostic}");
     }
 }
 
-/// Check if a version string contains pre-release identifiers
-fn is_prerelease_version(version: &str) -> bool {
-    version.contains('-')
-        && (version.contains("alpha") ||
-        version.contains("beta") ||
-        version.contains("rc") ||
-        version.contains("pre") ||
-        version.contains("dev") ||
-        // Also catch numbered pre-releases like "1.0.0-1"
-        version.split('-').nth(1).is_some_and(|part| {
-            part.chars().next().is_some_and(|c| c.is_ascii_digit())
-        }))
+/// Report requirement lint findings, grouped under one heading like diagnostics.
+pub fn print_lints(lints: &[LintFinding]) {
+    if lints.is_empty() {
+        return;
+    }
+
+    println!("\n📏 Requirement lints:");
+    for lint in lints {
+        println!("  {lint}");
+    }
+}
+
+/// Summarize crates whose lookup failed, one line per crate, and return how many there were.
+pub fn print_lookup_failures(results: &[Dependency]) -> usize {
+    let mut seen = std::collections::HashSet::new();
+    let failures: Vec<_> = results
+        .iter()
+        .filter_map(|dep| match &dep.status {
+            Status::LookupFailed(e) => Some((&dep.name, e)),
+            _ => None,
+        })
+        .filter(|(name, _)| seen.insert(*name))
+        .collect();
+
+    if failures.is_empty() {
+        return 0;
+    }
+
+    println!("\n❌ Lookups failed for {} crates:", failures.len());
+    for (name, e) in &failures {
+        println!("  {name}: {e}");
+    }
+    failures.len()
+}
+
+/// Report dependencies the vendored mirror cannot satisfy.
+pub fn print_vendor_lag(lagging: &[VendorLag]) {
+    if lagging.is_empty() {
+        println!("📦 All vendored copies satisfy their Cargo.toml requirements");
+        return;
+    }
+
+    println!("\n📦 Vendored copies lagging Cargo.toml requirements:");
+    for lag in lagging {
+        let vendored = if lag.vendored.is_empty() {
+            "not vendored".to_string()
+        } else {
+            format!("vendored {}", lag.vendored.join(", "))
+        };
+        println!(
+            "  {} {} ({}): {vendored}",
+            lag.name, lag.requirement, lag.source
+        );
+    }
+    println!(
+        "⚠️  Found {} requirements the vendored mirror cannot satisfy",
+        lagging.len()
+    );
 }
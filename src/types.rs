@@ -1,15 +1,87 @@
-use crate::version::comparison::is_version_outdated;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrateInfo {
     #[serde(rename = "crate")]
     pub crate_info: CrateDetails,
+    #[serde(default)]
+    pub versions: Vec<PublishedVersion>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrateDetails {
     pub max_version: String,
+    #[serde(default)]
+    pub downloads: Option<u64>,
+    #[serde(default)]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl CrateInfo {
+    /// Registry metadata as it applies to `version` (normally the latest one).
+    pub fn metadata(&self, version: Option<&str>) -> CrateMetadata {
+        let release = version.and_then(|v| self.versions.iter().find(|pv| pv.num == v));
+        CrateMetadata {
+            downloads: self.crate_info.downloads,
+            repository: self.crate_info.repository.clone(),
+            released: release
+                .and_then(|r| r.created_at.as_deref())
+                .map(|date| date.chars().take(10).collect()),
+            license: release.and_then(|r| r.license.clone()),
+        }
+    }
+}
+
+/// A single published release of a crate, as recorded by an index, the crates.io API or a snapshot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishedVersion {
+    pub num: String,
+    #[serde(default)]
+    pub yanked: bool,
+    /// Publish timestamp; only rich sources (crates.io API, db-dump) know it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Minimum supported Rust version the release declares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
+    /// Features the release offers, implicit optional-dependency features included;
+    /// only index sources know them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    /// What the `default` feature enables, when the feature table is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_features: Option<Vec<String>>,
+    /// Login of the user who published the release; only the crates.io API knows
+    /// it, and only for releases published after it started recording publishers
+    #[serde(default, skip_serializing, deserialize_with = "publisher_login")]
+    pub published_by: Option<String>,
+}
+
+/// The API records a publisher as a user object; keep just the login.
+fn publisher_login<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    struct User {
+        login: String,
+    }
+    Ok(Option::<User>::deserialize(deserializer)?.map(|user| user.login))
+}
+
+/// Popularity and provenance data for a dependency, when a rich data source is available.
+#[derive(Debug, Clone)]
+pub struct CrateMetadata {
+    pub downloads: Option<u64>,
+    pub repository: Option<String>,
+    /// Release date (`YYYY-MM-DD`) of the latest version
+    pub released: Option<String>,
+    /// License of the latest version
+    pub license: Option<String>,
 }
 
 #[derive(Debug)]
@@ -19,9 +91,562 @@ pub struct Dependency {
     pub latest_version: Option<String>,
     pub dep_type: DependencyType,
     pub source: String,
+    pub status: Status,
+    pub metadata: Option<CrateMetadata>,
+    /// Advisories affecting the locked version or the requirement's minimum version
+    pub advisories: Vec<AdvisoryMatch>,
+    /// Present when the declaring package has a `rust-version`
+    pub msrv: Option<MsrvCheck>,
+    /// Crates the upgrade to the latest version adds to and removes from the
+    /// dependency tree, with `--transitive`
+    pub tree_change: Option<(usize, usize)>,
+    /// How the feature table changes from the current to the latest version
+    pub feature_changes: Option<FeatureChanges>,
+    /// What `cargo semver-checks` found between the current and latest version,
+    /// with `--semver-checks`
+    pub semver_check: Option<SemverCheck>,
+    /// How the license changes from the current to the latest version, with `--licenses`
+    pub license_change: Option<LicenseChange>,
+    /// Who published the current and latest version and how the owners changed,
+    /// with `--publishers`
+    pub publisher_change: Option<PublisherChange>,
+}
+
+/// A different publisher for the latest version, or owners that changed since
+/// the last run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublisherChange {
+    /// The release compared from: the locked one, or the newest the requirement allows
+    pub from: String,
+    /// crates.io login that published `from`, when recorded
+    pub current_publisher: Option<String>,
+    /// crates.io login that published the latest version, when recorded
+    pub latest_publisher: Option<String>,
+    pub owners_added: Vec<String>,
+    pub owners_removed: Vec<String>,
+}
+
+impl PublisherChange {
+    /// Whether both releases record a publisher and they differ.
+    pub fn publisher_changed(&self) -> bool {
+        matches!((&self.current_publisher, &self.latest_publisher), (Some(a), Some(b)) if a != b)
+    }
+
+    pub fn owners_changed(&self) -> bool {
+        !self.owners_added.is_empty() || !self.owners_removed.is_empty()
+    }
+}
+
+/// A license change between the current and latest version, or a latest license
+/// outside the configured allow-list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LicenseChange {
+    /// The release compared from: the locked one, or the newest the requirement allows
+    pub from: String,
+    /// License of `from`, when known
+    pub current: Option<String>,
+    /// License of the latest version
+    pub latest: String,
+    /// Whether `allowed-licenses` permits the latest license; `None` without an allow-list
+    pub allowed: Option<bool>,
+}
+
+/// The outcome of running `cargo semver-checks` on an upgrade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemverCheck {
+    /// The release compared from: the locked one, or the newest the requirement allows
+    pub from: String,
+    /// Whether semver allows the jump without API breaks (`1.2` → `1.9`, not `0.4` → `0.5`)
+    pub compatible: bool,
+    /// Failed lints and how often each fired, or why the check could not run
+    pub breaks: Result<Vec<(String, usize)>, String>,
+}
+
+/// Differences between the feature tables of the current and the latest version.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureChanges {
+    /// The release compared from: the locked one, or the newest the requirement allows
+    pub from: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub default_added: Vec<String>,
+    pub default_removed: Vec<String>,
+    /// Features the manifests enable that the latest version no longer offers
+    pub enabled_missing: Vec<String>,
+}
+
+impl FeatureChanges {
+    /// Whether the upgrade breaks or changes what we build: a feature we enable
+    /// is gone, or the default set differs.
+    pub fn affects_build(&self) -> bool {
+        !self.enabled_missing.is_empty()
+            || !self.default_added.is_empty()
+            || !self.default_removed.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && !self.affects_build()
+    }
+}
+
+/// How a dependency's releases fit the `rust-version` of the package declaring it.
+#[derive(Debug, Clone)]
+pub struct MsrvCheck {
+    /// Newest release whose declared `rust-version` fits ours
+    pub latest_usable: Option<String>,
+    /// `rust-version` the latest release declares, when it is newer than ours
+    pub latest_requires: Option<String>,
+}
+
+/// How a dependency's requirement relates to the latest published version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    UpToDate,
+    /// A newer version satisfies the existing requirement; `cargo update` picks it up
+    CompatibleUpdate,
+    /// The latest version is semver-incompatible with the requirement: `1.2` → `2.0`,
+    /// or `0.3` → `0.4`
+    MajorUpdate,
+    /// A compatible minor release the requirement excludes, e.g. `~1.2` → `1.4`
+    MinorUpdate,
+    /// A patch release the requirement excludes, e.g. `=1.2.3` → `1.2.5`
+    PatchUpdate,
+    /// The latest version is a pre-release newer than the requirement
+    PrereleaseAvailable,
+    /// The required or locked `version` was yanked; `replacement` is the nearest
+    /// compatible release that was not
+    Yanked {
+        version: String,
+        replacement: Option<String>,
+    },
+    /// No source knows the crate
+    NotFound,
+    /// The lookup itself failed, so nothing is known about the crate
+    LookupFailed(LookupError),
+    /// `*` or a bare lower bound: any newer release is already allowed
+    Unpinned,
+}
+
+impl Status {
+    /// Whether a newer version is available, whether the requirement already
+    /// allows it (`cargo update` picks it up) or the manifest has to change.
+    pub fn is_outdated(&self) -> bool {
+        matches!(
+            self,
+            Status::CompatibleUpdate
+                | Status::MajorUpdate
+                | Status::MinorUpdate
+                | Status::PatchUpdate
+                | Status::PrereleaseAvailable
+        )
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::UpToDate => write!(f, "✅ Latest"),
+            Status::CompatibleUpdate => write!(f, "🟢 Outdated (compatible)"),
+            Status::MajorUpdate => write!(f, "🔴 Outdated (major)"),
+            Status::MinorUpdate => write!(f, "🔴 Outdated (minor)"),
+            Status::PatchUpdate => write!(f, "🔴 Outdated (patch)"),
+            Status::PrereleaseAvailable => write!(f, "🟡 Pre-release available"),
+            Status::Yanked {
+                version,
+                replacement: Some(replacement),
+            } => write!(f, "⛔ Yanked {version} (use {replacement})"),
+            Status::Yanked {
+                version,
+                replacement: None,
+            } => write!(f, "⛔ Yanked {version} (no replacement)"),
+            Status::NotFound => write!(f, "❓ Not found"),
+            Status::LookupFailed(reason) => write!(f, "❌ Lookup failed: {reason}"),
+            Status::Unpinned => write!(f, "⚪ Unpinned"),
+        }
+    }
+}
+
+/// Why a crate's published versions could not be looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    /// crates.io answered 404: the crate does not exist
+    NotFound,
+    /// The local index has no entry for the crate
+    NotInIndex,
+    /// The `--snapshot` file was exported without the crate
+    NotInSnapshot,
+    /// The local index entry exists but could not be read
+    Index(String),
+    Timeout,
+    /// Still rate limited (HTTP 429) after every retry
+    RateLimited,
+    Http(u16),
+    Network(String),
+    /// The response body was not the expected JSON
+    InvalidResponse(String),
+    /// The lookup task panicked or was cancelled
+    Task(String),
+    /// Versions were listed, but none of them can be reported as the latest
+    NoUsableRelease(String),
+}
+
+impl std::fmt::Display for LookupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LookupError::NotFound => write!(f, "not found on crates.io"),
+            LookupError::NotInIndex => write!(f, "not in local index"),
+            LookupError::NotInSnapshot => write!(f, "not in snapshot"),
+            LookupError::Index(e) => write!(f, "unreadable index entry: {e}"),
+            LookupError::Timeout => write!(f, "timed out"),
+            LookupError::RateLimited => write!(f, "rate limited"),
+            LookupError::Http(status) => write!(f, "HTTP {status}"),
+            LookupError::Network(e) => write!(f, "network error: {e}"),
+            LookupError::InvalidResponse(e) => write!(f, "invalid response: {e}"),
+            LookupError::Task(e) => write!(f, "task failed: {e}"),
+            LookupError::NoUsableRelease(reason) => write!(f, "no usable release: {reason}"),
+        }
+    }
+}
+
+/// Published versions per crate, plus why each failed lookup failed.
+#[derive(Debug, Default)]
+pub struct VersionLookup {
+    pub versions: HashMap<String, Vec<PublishedVersion>>,
+    pub failures: HashMap<String, LookupError>,
+    /// Read from Cargo's own index cache, which lags behind the registry
+    pub from_local_cache: bool,
+}
+
+impl From<HashMap<String, Vec<PublishedVersion>>> for VersionLookup {
+    fn from(versions: HashMap<String, Vec<PublishedVersion>>) -> Self {
+        VersionLookup {
+            versions,
+            ..VersionLookup::default()
+        }
+    }
+}
+
+/// A dependency whose requirement no copy in the vendored mirror satisfies.
+#[derive(Debug)]
+pub struct VendorLag {
+    pub name: String,
+    pub requirement: String,
+    pub source: String,
+    /// Versions present in the mirror; empty when the crate is not vendored at all
+    pub vendored: Vec<String>,
+}
+
+/// How serious an advisory is: a CVSS v3 band, or the kind of an informational advisory.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AdvisorySeverity {
+    /// `unmaintained`, `unsound`, ...: not a vulnerability
+    Informational(String),
+    /// A vulnerability without a CVSS v3 vector
+    Unrated,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl AdvisorySeverity {
+    pub fn is_vulnerability(&self) -> bool {
+        !matches!(self, AdvisorySeverity::Informational(_))
+    }
+}
+
+impl std::fmt::Display for AdvisorySeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdvisorySeverity::Informational(kind) => write!(f, "{kind}"),
+            AdvisorySeverity::Unrated => write!(f, "unrated"),
+            AdvisorySeverity::Low => write!(f, "low"),
+            AdvisorySeverity::Medium => write!(f, "medium"),
+            AdvisorySeverity::High => write!(f, "high"),
+            AdvisorySeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A `RustSec` advisory that applies to a checked version.
+#[derive(Debug, Clone)]
+pub struct AdvisoryMatch {
+    pub id: String,
+    pub severity: AdvisorySeverity,
+    /// Lowest version above the checked one that the advisory no longer affects
+    pub fixed_in: Option<String>,
+}
+
+impl std::fmt::Display for AdvisoryMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.fixed_in {
+            Some(fixed) => write!(f, "{} ({}, fixed in {fixed})", self.id, self.severity),
+            None => write!(f, "{} ({}, no fix)", self.id, self.severity),
+        }
+    }
+}
+
+/// A Cargo.lock entry whose locked version has advisories.
+#[derive(Debug)]
+pub struct VulnerablePackage {
+    pub name: String,
+    pub version: String,
+    pub advisories: Vec<AdvisoryMatch>,
+}
+
+/// A locked package declaring a newer `rust-version` than the workspace does.
+#[derive(Debug)]
+pub struct MsrvBlocker {
+    pub name: String,
+    pub version: String,
+    pub rust_version: String,
+    /// Newest semver-compatible release that fits the declared `rust-version`
+    pub downgrade: Option<String>,
+}
+
+/// The minimum Rust version the locked dependency graph needs.
+#[derive(Debug)]
+pub struct MsrvReport {
+    pub declared: Option<String>,
+    /// Highest `rust-version` among locked packages
+    pub effective: Option<String>,
+    /// The locked package (`name version`) declaring the effective `rust-version`
+    pub set_by: Option<String>,
+    pub blockers: Vec<MsrvBlocker>,
+    /// Locked packages whose `rust-version` is unknown: none declared, or the lookup failed
+    pub unknown: usize,
+    pub locked: usize,
+}
+
+/// A Cargo.lock entry whose locked version was yanked.
+#[derive(Debug)]
+pub struct YankedPackage {
+    pub name: String,
+    pub version: String,
+    pub replacement: Option<String>,
+}
+
+/// Features a manifest enables on a dependency, which every version the
+/// requirement allows must offer.
+#[derive(Debug, Clone)]
+pub struct FeatureRequest {
+    pub name: String,
+    pub requirement: String,
+    pub features: Vec<String>,
+    /// The package (or `root`) enabling them
+    pub source: String,
+}
+
+/// A requested feature missing from some version the requirement allows. Features
+/// the latest release dropped are part of [`FeatureChanges`] instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureProblem {
+    /// The lowest allowed version lacks the features; `tightened` is the first
+    /// allowed version offering them all
+    MissingAtLowerBound {
+        name: String,
+        source: String,
+        features: Vec<String>,
+        lowest: String,
+        tightened: Option<String>,
+    },
+}
+
+/// An entry of a published release's own dependency list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReleaseDependency {
+    /// Crate name, even when the release renames it
+    pub name: String,
+    pub requirement: String,
+    pub kind: DependencyType,
+    pub optional: bool,
+}
+
+/// How one entry of a crate's dependency list changed between two releases.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyChange {
+    Added(ReleaseDependency),
+    Removed(ReleaseDependency),
+    /// The requirement changed from `from` to the one in `dependency`
+    Requirement {
+        dependency: ReleaseDependency,
+        from: String,
+    },
+    BecameOptional(ReleaseDependency),
+    BecameRequired(ReleaseDependency),
+}
+
+/// A release's section of a changelog: its heading line and the text under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangelogSection {
+    pub version: String,
+    pub heading: String,
+    pub body: String,
+}
+
+/// The changelog sections between two releases, read from the newer one's
+/// cached source.
+#[derive(Debug)]
+pub struct ChangelogExcerpt {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    /// The changelog file, e.g. `CHANGELOG.md`
+    pub file: String,
+    /// Where the source was found
+    pub source: String,
+    pub sections: Vec<ChangelogSection>,
+}
+
+/// What moving a crate from one release to another changes in what it pulls in.
+#[derive(Debug)]
+pub struct DependencyDiff {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+    pub changes: Vec<DependencyChange>,
+    /// Crates only the new release's dependency tree contains
+    pub added_crates: Vec<String>,
+    /// Crates only the old release's dependency tree contains
+    pub removed_crates: Vec<String>,
+}
+
+/// One entry for a package in a manifest's dependency tables, target tables included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRequirement {
+    /// The package on crates.io, which differs from the key for renamed entries
+    pub package: String,
+    /// The table holding the entry, e.g. `dev-dependencies` or
+    /// `target.'cfg(windows)'.dependencies`
+    pub table: String,
+    pub requirement: String,
+}
+
+/// A package one manifest declares with differing requirements across its tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestConflict {
+    /// The package (or `root`) whose manifest holds the entries
+    pub source: String,
+    pub package: String,
+    /// (table, requirement) of every entry
+    pub declarations: Vec<(String, String)>,
+    pub resolution: ConflictResolution,
+}
+
+/// What Cargo makes of a package declared with differing requirements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConflictResolution {
+    /// One version satisfies every entry, so the entry with the highest minimum
+    /// decides; `version` is the release it resolves to, when versions are known
+    Unified {
+        requirement: String,
+        version: Option<String>,
+    },
+    /// No single release satisfies every entry; `suggested` is the newest requirement
+    Incompatible { suggested: String },
+}
+
+/// Packages that cannot coexist because they link the same native library.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinksConflict {
+    /// Upgrading `dependency` to `version` brings in `incoming` (`name version`)
+    /// while `existing` keeps linking `links` from elsewhere in the lock graph
+    Upgrade {
+        dependency: String,
+        version: String,
+        links: String,
+        incoming: String,
+        existing: String,
+    },
+    /// `package` is shared by several `dependents`, and its latest release is
+    /// semver-incompatible: none of them can move to it alone
+    Lockstep {
+        links: String,
+        package: String,
+        latest: String,
+        dependents: Vec<String>,
+    },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// A crate locked in several semver-incompatible versions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateCrate {
+    pub name: String,
+    pub newest: String,
+    /// The older copies, the ones to get rid of
+    pub copies: Vec<DuplicateCopy>,
+}
+
+/// An older copy of a duplicated crate and the packages holding on to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateCopy {
+    pub version: String,
+    pub holders: Vec<DuplicateHolder>,
+}
+
+impl DuplicateCopy {
+    /// Whether upgrading every holder to its latest release drops this copy. A
+    /// workspace member holding it needs a manifest edit, not an upgrade.
+    pub fn is_removable(&self) -> bool {
+        !self.holders.is_empty()
+            && self
+                .holders
+                .iter()
+                .all(|holder| matches!(holder.fix, HolderFix::Upgrade(_)))
+    }
+}
+
+/// A locked package depending on an old copy of a duplicated crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateHolder {
+    pub name: String,
+    pub version: String,
+    pub fix: HolderFix,
+}
+
+/// What upgrading a holder of an old copy does for the duplicate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HolderFix {
+    /// Its latest release (this version) requires the newest copy, or nothing
+    Upgrade(String),
+    /// A workspace member: raise the requirement in its manifest
+    Member,
+    /// Even its latest release requires an old copy
+    Stuck { latest: String, requirement: String },
+    /// The index does not know the holder's releases
+    Unknown,
+}
+
+/// A member's own declaration of a dependency: the member, the table and the requirement.
+pub type MemberRequirement = (String, DependencyType, String);
+
+/// An inconsistency in how workspace members declare their dependencies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HygieneFinding {
+    /// Members (or `[workspace.dependencies]`, as `workspace`) require different versions
+    Mismatch {
+        name: String,
+        requirements: Vec<(String, String)>,
+    },
+    /// Several members declare it themselves: it belongs in `[workspace.dependencies]`
+    MoveToWorkspace {
+        name: String,
+        requirement: String,
+        members: Vec<MemberRequirement>,
+    },
+    /// `[workspace.dependencies]` already has it: the member should use `workspace = true`
+    UseWorkspace {
+        name: String,
+        member: MemberRequirement,
+        workspace_requirement: String,
+    },
+}
+
+/// A dependency as declared in a manifest: name, requirement, kind and the
+/// package (or `root`) declaring it.
+pub type DeclaredDependency = (String, String, DependencyType, String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DependencyType {
     Normal,
     Dev,
@@ -42,10 +667,6 @@ impl std::fmt::Display for DependencyType {
 
 impl Dependency {
     pub fn is_outdated(&self) -> bool {
-        if let Some(latest) = &self.latest_version {
-            is_version_outdated(&self.current_version, latest)
-        } else {
-            false
-        }
+        self.status.is_outdated()
     }
 }
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub fn ensure_cargo_toml_path(path_str: &'_ str) -> Cow<'_, str> {
     let path = Path::new(path_str);
@@ -14,3 +14,68 @@ pub fn ensure_cargo_toml_path(path_str: &'_ str) -> Cow<'_, str> {
         Cow::Borrowed(path_str)
     }
 }
+
+/// Format seconds since the Unix epoch as a `YYYY-MM-DD` date (UTC).
+pub fn format_unix_date(secs: u64) -> String {
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let days = i64::try_from(secs / 86_400).unwrap_or(i64::MAX / 2);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Cargo's home directory: `$CARGO_HOME`, falling back to `~/.cargo`.
+pub fn cargo_home() -> PathBuf {
+    std::env::var("CARGO_HOME").map_or_else(
+        |_| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+            PathBuf::from(home).join(".cargo")
+        },
+        PathBuf::from,
+    )
+}
+
+/// Whether a directory under `$CARGO_HOME/registry/{index,src,cache}` belongs to
+/// crates.io: `index.crates.io-<hash>` (sparse) or `github.com-<hash>` (git).
+pub fn is_crates_io_registry(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with("index.crates.io-") || n.starts_with("github.com-"))
+}
+
+/// Directory for cargo-stale's own caches: `$XDG_CACHE_HOME/cargo-stale`,
+/// falling back to `~/.cache/cargo-stale`.
+pub fn cache_dir() -> PathBuf {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(
+            || {
+                let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+                PathBuf::from(home).join(".cache")
+            },
+            PathBuf::from,
+        )
+        .join("cargo-stale")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_unix_date() {
+        assert_eq!(format_unix_date(0), "1970-01-01");
+        // Leap days, including the 400-year rule's 2000
+        assert_eq!(format_unix_date(1_709_208_000), "2024-02-29");
+        assert_eq!(format_unix_date(951_782_400), "2000-02-29");
+        // The last second of a day stays on it
+        assert_eq!(format_unix_date(1_792_367_999), "2026-10-18");
+    }
+}
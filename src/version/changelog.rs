@@ -0,0 +1,130 @@
+use crate::types::ChangelogSection;
+use crate::version::core::Version;
+
+/// The release a Markdown heading is about, e.g. `1.2.3` in `## [1.2.3] - 2024-05-01`
+/// or `# v0.4.0 (May 1st)`.
+fn heading_version(heading: &str) -> Option<Version> {
+    heading
+        .split(|c: char| c.is_whitespace() || "[]()".contains(c))
+        .map(|word| word.trim_start_matches('v').trim_end_matches(':'))
+        .filter(|word| word.contains('.'))
+        .find_map(Version::parse)
+}
+
+/// A Markdown heading: where it starts, where the text under it starts, its
+/// depth (1 for `#` or `===` underlines) and its text.
+struct Heading<'a> {
+    line: usize,
+    body: usize,
+    depth: usize,
+    text: &'a str,
+}
+
+/// ATX (`## 1.2.0`) and setext (`1.2.0` underlined with `===` or `---`) headings,
+/// skipping fenced code, where `# ...` is a comment or attribute.
+fn headings<'a>(lines: &[&'a str]) -> Vec<Heading<'a>> {
+    let underline = |line: &str, c: char| {
+        let line = line.trim();
+        line.len() >= 3 && line.chars().all(|l| l == c)
+    };
+
+    let mut headings = Vec::new();
+    let mut in_code = false;
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+        }
+        if in_code || line.trim().is_empty() {
+            continue;
+        }
+        let hashes = line.chars().take_while(|c| *c == '#').count();
+        let setext = lines.get(index + 1).and_then(|next| {
+            [('=', 1), ('-', 2)]
+                .into_iter()
+                .find(|(c, _)| underline(next, *c))
+                .map(|(_, depth)| depth)
+        });
+        if let Some(text) = line[hashes..].strip_prefix(' ')
+            && hashes > 0
+        {
+            headings.push(Heading {
+                line: index,
+                body: index + 1,
+                depth: hashes,
+                text: text.trim(),
+            });
+        } else if let Some(depth) = setext
+            && hashes == 0
+        {
+            headings.push(Heading {
+                line: index,
+                body: index + 2,
+                depth,
+                text: line.trim(),
+            });
+        }
+    }
+    headings
+}
+
+/// Sections of a Markdown changelog for the releases after `from`, up to and
+/// including `to`, newest first as changelogs list them.
+///
+/// Release headings are the shallowest headings that name a version; a section
+/// runs until the next heading at that depth or above, so `### Fixed` style
+/// subsections stay in their release.
+pub fn excerpt(content: &str, from: &Version, to: &Version) -> Vec<ChangelogSection> {
+    let lines: Vec<&str> = content.lines().collect();
+    let headings = headings(&lines);
+    let Some(release_depth) = headings
+        .iter()
+        .filter(|heading| heading_version(heading.text).is_some())
+        .map(|heading| heading.depth)
+        .min()
+    else {
+        return Vec::new();
+    };
+
+    let mut sections = Vec::new();
+    for (position, heading) in headings.iter().enumerate() {
+        if heading.depth != release_depth {
+            continue;
+        }
+        let Some(version) = heading_version(heading.text).filter(|v| v > from && v <= to) else {
+            continue;
+        };
+        let end = headings[position + 1..]
+            .iter()
+            .find(|next| next.depth <= release_depth)
+            .map_or(lines.len(), |next| next.line);
+        sections.push(ChangelogSection {
+            version: version.to_string(),
+            heading: heading.text.to_string(),
+            body: lines[heading.body..end].join("\n").trim().to_string(),
+        });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt() {
+        let changelog = "# Changelog\n\n## Unreleased\n\n## [1.3.0] - 2024-05-01\n### Added\n- `retry`\n\n## v1.2.1\nFixed a panic.\n\n## 1.2.0 (2024-01-10)\nInitial async API.\n```sh\n# 1.1.0 is not a heading\n```\n";
+        let parse = |v| Version::parse(v).unwrap();
+
+        let sections = excerpt(changelog, &parse("1.2.0"), &parse("1.3.0"));
+        let versions: Vec<&str> = sections.iter().map(|s| s.version.as_str()).collect();
+        assert_eq!(versions, ["1.3.0", "1.2.1"]);
+        assert_eq!(sections[0].heading, "[1.3.0] - 2024-05-01");
+        assert_eq!(sections[0].body, "### Added\n- `retry`");
+        assert!(excerpt(changelog, &parse("1.3.0"), &parse("1.3.0")).is_empty());
+
+        let setext = "1.1.0 (2024-02-01)\n==================\nFaster.\n\nBug fixes:\n\n1.0.0\n=====\nFirst.\n";
+        let sections = excerpt(setext, &parse("1.0.0"), &parse("1.1.0"));
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].body, "Faster.\n\nBug fixes:");
+    }
+}
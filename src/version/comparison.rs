@@ -1,41 +1,181 @@
+use crate::cli::PrereleasePolicy;
+use crate::types::{PublishedVersion, Status};
 use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
 
 /// Check if a dependency version requirement is outdated compared to the latest version.
 ///
-/// Uses prefix matching: if Cargo.toml specifies a partial version like "1" or "1.2",
-/// any version matching that prefix is considered compatible (not outdated).
-/// - "1" matches all 1.x.x → not outdated
-/// - "1.2" matches all 1.2.x → not outdated
-/// - "1.2.3" only matches 1.2.3 exactly → outdated if latest is higher
+/// A requirement is up to date when its minimum is the latest version, or when it
+/// is written no more precisely than it cares about (see [`VersionReq::is_current`]):
+/// - "1" or "^1" matches all 1.x.x → not outdated
+/// - "1.2" or "~1.2" matches all 1.2.x → not outdated
+/// - "1.2.3", "=1.2.3" or "~1.2.3" → outdated if latest is higher
 pub fn is_version_outdated(current_req: &str, latest_version: &str) -> bool {
     let current_req = current_req.trim();
-    if current_req == "*" {
-        return false;
+    match (
+        VersionReq::parse(current_req),
+        Version::parse(latest_version),
+    ) {
+        (Some(req), Some(latest)) => !req.is_current(&latest),
+        _ => current_req != latest_version,
     }
+}
 
-    let Some(current) = Version::parse(current_req) else {
-        return current_req != latest_version;
-    };
+/// Pick the highest non-yanked release, skipping pre-releases unless `allow_prerelease`.
+pub fn select_latest(versions: &[PublishedVersion], allow_prerelease: bool) -> Option<&str> {
+    select_latest_where(versions, allow_prerelease, |_| true)
+}
 
-    let Some(latest) = Version::parse(latest_version) else {
-        return current_req != latest_version;
-    };
+fn select_latest_where(
+    versions: &[PublishedVersion],
+    allow_prerelease: bool,
+    keep: impl Fn(&PublishedVersion) -> bool,
+) -> Option<&str> {
+    versions
+        .iter()
+        .filter(|v| !v.yanked && keep(v))
+        .filter_map(|v| Version::parse(&v.num).map(|parsed| (parsed, v.num.as_str())))
+        .filter(|(parsed, _)| allow_prerelease || parsed.pre_release.is_none())
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, num)| num)
+}
 
-    // Prefix matching: if current has no minor, only compare major
-    // If current has no patch, only compare major.minor
-    if current.minor.is_none() {
-        // "1" → any 1.x.x is compatible
-        return current.major != latest.major;
+/// Pick the latest version to report for a dependency.
+///
+/// This is the one place the pre-release policy is applied, whichever source the
+/// version list came from: pre-releases only count with `--pre`, or when the
+/// requirement itself already names a pre-release (`4.0.0-rc.3` → `4.0.0-rc.5`).
+pub fn latest_for_requirement<'a>(
+    versions: &'a [PublishedVersion],
+    current_req: &str,
+    policy: PrereleasePolicy,
+) -> Option<&'a str> {
+    let requirement_is_prerelease =
+        VersionReq::parse(current_req).is_some_and(|req| req.names_prerelease());
+    select_latest(
+        versions,
+        policy.includes_prereleases() || requirement_is_prerelease,
+    )
+}
+
+/// The newest non-yanked release `current_req` allows, i.e. what a fresh build picks.
+pub fn newest_matching<'a>(versions: &'a [PublishedVersion], current_req: &str) -> Option<&'a str> {
+    let requirement = VersionReq::parse(current_req)?;
+    select_latest_where(versions, true, |v| requirement.matches_str(&v.num))
+}
+
+/// Like [`latest_for_requirement`], but only among releases whose declared
+/// `rust-version` is at most `msrv`. Releases declaring none are assumed to fit.
+pub fn latest_usable<'a>(
+    versions: &'a [PublishedVersion],
+    current_req: &str,
+    msrv: &Version,
+    policy: PrereleasePolicy,
+) -> Option<&'a str> {
+    let requirement_is_prerelease =
+        VersionReq::parse(current_req).is_some_and(|req| req.names_prerelease());
+    select_latest_where(
+        versions,
+        policy.includes_prereleases() || requirement_is_prerelease,
+        |v| !needs_newer_rust(v, msrv),
+    )
+}
+
+/// Whether a release declares a `rust-version` newer than `msrv`.
+pub fn needs_newer_rust(version: &PublishedVersion, msrv: &Version) -> bool {
+    version
+        .rust_version
+        .as_deref()
+        .and_then(Version::parse)
+        .is_some_and(|required| required > *msrv)
+}
+
+/// Every version semver-compatible with `version`, older ones included:
+/// `^1` for `1.4.2`, `^0.4` for `0.4.2`, `=0.0.3` for `0.0.3`.
+pub fn compatible_range(version: &Version) -> Option<VersionReq> {
+    VersionReq::parse(&match (version.major, version.minor.unwrap_or(0)) {
+        (0, 0) => format!("=0.0.{}", version.patch.unwrap_or(0)),
+        (0, minor) => format!("^0.{minor}"),
+        (major, _) => format!("^{major}"),
+    })
+}
+
+/// The closest release to a yanked `version` that is not yanked and stays
+/// semver-compatible with it: the next one up, or failing that the previous one.
+pub fn nearest_replacement<'a>(versions: &'a [PublishedVersion], version: &str) -> Option<&'a str> {
+    let yanked = Version::parse(version)?;
+    let compatible = compatible_range(&yanked)?;
+
+    let candidates: Vec<(Version, &str)> = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Version::parse(&v.num).map(|parsed| (parsed, v.num.as_str())))
+        .filter(|(parsed, _)| parsed.pre_release.is_none() && compatible.matches(parsed))
+        .collect();
+
+    let above = candidates
+        .iter()
+        .filter(|(parsed, _)| *parsed > yanked)
+        .min_by(|(a, _), (b, _)| a.cmp(b));
+    let below = candidates
+        .iter()
+        .filter(|(parsed, _)| *parsed < yanked)
+        .max_by(|(a, _), (b, _)| a.cmp(b));
+    above.or(below).map(|(_, num)| *num)
+}
+
+/// Classify a dependency whose latest version is known.
+///
+/// `versions` is the crate's full version list, used to spot requirements that
+/// only allow yanked releases.
+pub fn classify(current_req: &str, latest: &str, versions: &[PublishedVersion]) -> Status {
+    let req = VersionReq::parse(current_req);
+    if req.as_ref().is_some_and(VersionReq::is_unbounded) {
+        return Status::Unpinned;
     }
 
-    if current.patch.is_none() {
-        // "1.2" → any 1.2.x is compatible
-        return current.major != latest.major
-            || current.minor != latest.minor;
+    if let Some(req) = &req {
+        let allowed: Vec<&PublishedVersion> = versions
+            .iter()
+            .filter(|v| req.matches_str(&v.num))
+            .collect();
+        if !allowed.is_empty() && allowed.iter().all(|v| v.yanked) {
+            let version = allowed
+                .iter()
+                .filter_map(|v| Version::parse(&v.num).map(|parsed| (parsed, &v.num)))
+                .max_by(|(a, _), (b, _)| a.cmp(b))
+                .map_or_else(|| current_req.to_string(), |(_, num)| num.clone());
+            return Status::Yanked {
+                replacement: nearest_replacement(versions, &version).map(str::to_string),
+                version,
+            };
+        }
     }
 
-    // Full version "1.2.3" → exact comparison
-    current < latest
+    let Some(latest) = Version::parse(latest) else {
+        return Status::NotFound;
+    };
+    if !is_version_outdated(current_req, &latest.to_string()) {
+        return Status::UpToDate;
+    }
+    if latest.is_prerelease() {
+        return Status::PrereleaseAvailable;
+    }
+    if req.as_ref().is_some_and(|r| r.matches(&latest)) {
+        return Status::CompatibleUpdate;
+    }
+
+    // Breaking or not is semver's call: `0.3` → `0.4` is as breaking as `1` → `2`
+    let Some(base) = req.as_ref().and_then(VersionReq::lower_bound) else {
+        return Status::MajorUpdate;
+    };
+    if !compatible_range(&base).is_some_and(|range| range.matches(&latest)) {
+        Status::MajorUpdate
+    } else if base.minor.unwrap_or(0) == latest.minor.unwrap_or(0) {
+        Status::PatchUpdate
+    } else {
+        Status::MinorUpdate
+    }
 }
 
 #[cfg(test)]
@@ -49,6 +189,7 @@ mod tests {
         assert!(!is_version_outdated("0.7.1", "0.5.92"));
         assert!(is_version_outdated("4.0.0-rc.3", "4.0.0"));
         assert!(!is_version_outdated("4.0.0", "4.0.0+build.123"));
+        assert!(!is_version_outdated("1.1", "1.1.8+spec-1.1.0"));
     }
 
     #[test]
@@ -73,6 +214,17 @@ mod tests {
         assert!(!is_version_outdated("1", "1.0"));
     }
 
+    #[test]
+    fn test_operator_requirements_up_to_date() {
+        for req in ["=1.2.5", "^1.2.5", "~1.2.5", "1.2.5", "^1", "~1.2", "1.2.*"] {
+            assert!(!is_version_outdated(req, "1.2.5"), "{req}");
+        }
+        assert!(is_version_outdated("=1.2.3", "1.2.5"));
+        assert!(is_version_outdated("~1.2.3", "1.2.5"));
+        assert!(is_version_outdated("^1.2", "1.3.0"));
+        assert!(is_version_outdated(">=1.0, <2", "1.2.5"));
+    }
+
     #[test]
     fn test_prerelease_semver_comparison() {
         // Numeric pre-release: alpha.10 > alpha.2
@@ -82,4 +234,101 @@ mod tests {
         // Numeric < alphanumeric
         assert!(is_version_outdated("1.0.0-1", "1.0.0-beta"));
     }
+
+    fn published(nums: &[&str]) -> Vec<PublishedVersion> {
+        nums.iter()
+            .map(|num| PublishedVersion {
+                num: (*num).to_string(),
+                ..PublishedVersion::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_prerelease_policy() {
+        let versions = published(&["3.9.0", "4.0.0-rc.3", "4.0.0-rc.5"]);
+        let exclude = PrereleasePolicy::Exclude;
+        assert_eq!(
+            latest_for_requirement(&versions, "3", exclude),
+            Some("3.9.0")
+        );
+        assert_eq!(
+            latest_for_requirement(&versions, "3", PrereleasePolicy::Include),
+            Some("4.0.0-rc.5")
+        );
+        // Already on a pre-release: pre-releases are included automatically
+        assert_eq!(
+            latest_for_requirement(&versions, "4.0.0-rc.3", exclude),
+            Some("4.0.0-rc.5")
+        );
+    }
+
+    #[test]
+    fn test_select_latest_skips_yanked() {
+        let mut versions = published(&["1.0.0", "1.1.0"]);
+        versions[1].yanked = true;
+        assert_eq!(select_latest(&versions, false), Some("1.0.0"));
+        assert_eq!(select_latest(&published(&["1.0.0-alpha.1"]), false), None);
+    }
+
+    #[test]
+    fn test_classify() {
+        let versions = published(&["1.2.3", "1.2.5", "1.4.0", "2.0.0", "3.0.0-beta.1"]);
+        assert_eq!(classify("2", "2.0.0", &versions), Status::UpToDate);
+        for req in ["=1.2.5", "^1.2.5", "~1.2.5"] {
+            assert_eq!(classify(req, "1.2.5", &versions), Status::UpToDate, "{req}");
+        }
+        assert_eq!(
+            classify("1.2.3", "1.4.0", &versions),
+            Status::CompatibleUpdate
+        );
+        assert_eq!(classify("=1.2.3", "1.2.5", &versions), Status::PatchUpdate);
+        assert_eq!(classify("~1.2", "1.4.0", &versions), Status::MinorUpdate);
+        assert_eq!(classify("1.2", "2.0.0", &versions), Status::MajorUpdate);
+        assert_eq!(classify("0.3", "0.4.0", &versions), Status::MajorUpdate);
+        assert_eq!(classify("0.0.3", "0.0.4", &versions), Status::MajorUpdate);
+        assert_eq!(classify("=0.3.1", "0.3.5", &versions), Status::PatchUpdate);
+        assert_eq!(
+            classify("2", "3.0.0-beta.1", &versions),
+            Status::PrereleaseAvailable
+        );
+        assert_eq!(classify("*", "2.0.0", &versions), Status::Unpinned);
+        assert_eq!(classify(">=1.0", "2.0.0", &versions), Status::Unpinned);
+
+        let mut yanked = published(&["1.0.0", "1.0.1"]);
+        yanked[0].yanked = true;
+        assert_eq!(
+            classify("=1.0.0", "1.0.1", &yanked),
+            Status::Yanked {
+                version: "1.0.0".to_string(),
+                replacement: Some("1.0.1".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_nearest_replacement() {
+        let mut versions = published(&["0.3.9", "0.4.0", "0.4.1", "0.4.2", "0.5.0"]);
+        versions[2].yanked = true;
+        versions[3].yanked = true;
+        assert_eq!(nearest_replacement(&versions, "0.4.1"), Some("0.4.0"));
+        versions[3].yanked = false;
+        assert_eq!(nearest_replacement(&versions, "0.4.1"), Some("0.4.2"));
+        assert_eq!(nearest_replacement(&versions, "0.5.0"), None);
+    }
+
+    #[test]
+    fn test_latest_usable() {
+        let mut versions = published(&["1.0.0", "1.1.0", "1.2.0", "1.3.0"]);
+        versions[1].rust_version = Some("1.60".to_string());
+        versions[2].rust_version = Some("1.70".to_string());
+        versions[3].rust_version = Some("1.80.1".to_string());
+        let msrv = Version::parse("1.70").unwrap();
+        assert_eq!(
+            latest_usable(&versions, "1", &msrv, PrereleasePolicy::Exclude),
+            Some("1.2.0")
+        );
+        assert!(needs_newer_rust(&versions[3], &msrv));
+        assert!(!needs_newer_rust(&versions[0], &msrv));
+    }
 }
@@ -13,32 +13,15 @@ impl Version {
     pub fn parse(version_str: &str) -> Option<Self> {
         let version_str = version_str.trim();
 
-        let (core_version, pre_and_build) = if let Some(pos) = version_str.find('-') {
-            (&version_str[..pos], Some(&version_str[pos + 1..]))
-        } else {
-            (version_str, None)
+        // Build metadata comes after the first '+' and may itself contain '-'
+        let (version_str, build) = match version_str.split_once('+') {
+            Some((rest, build)) => (rest, Some(build.to_string())),
+            None => (version_str, None),
         };
 
-        let (pre_release, build) = if let Some(pre_and_build) = pre_and_build {
-            if let Some(pos) = pre_and_build.find('+') {
-                (
-                    Some(pre_and_build[..pos].to_string()),
-                    Some(pre_and_build[pos + 1..].to_string()),
-                )
-            } else {
-                (Some(pre_and_build.to_string()), None)
-            }
-        } else if let Some(pos) = core_version.find('+') {
-            let (_core, build_part) = core_version.split_at(pos);
-            (None, Some(build_part[1..].to_string()))
-        } else {
-            (None, None)
-        };
-
-        let core_version = if let Some(pos) = core_version.find('+') {
-            &core_version[..pos]
-        } else {
-            core_version
+        let (core_version, pre_release) = match version_str.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (version_str, None),
         };
 
         let parts: Vec<&str> = core_version.split('.').collect();
@@ -60,6 +43,10 @@ impl Version {
             build,
         })
     }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.pre_release.is_some()
+    }
 }
 
 impl fmt::Display for Version {
@@ -155,3 +142,22 @@ fn compare_pre_release_identifiers(a: &str, b: &str) -> std::cmp::Ordering {
 
     a_parts.len().cmp(&b_parts.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_build_metadata() {
+        // Build metadata may contain '-', which must not start a pre-release
+        let version = Version::parse("1.1.8+spec-1.1.0").unwrap();
+        assert_eq!(version.pre_release, None);
+        assert_eq!(version.build.as_deref(), Some("spec-1.1.0"));
+        assert_eq!(version.patch, Some(8));
+
+        let version = Version::parse("4.0.0-rc.1+build-5").unwrap();
+        assert_eq!(version.pre_release.as_deref(), Some("rc.1"));
+        assert_eq!(version.build.as_deref(), Some("build-5"));
+        assert_eq!(version.to_string(), "4.0.0-rc.1+build-5");
+    }
+}
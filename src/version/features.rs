@@ -0,0 +1,153 @@
+use crate::types::{FeatureChanges, FeatureProblem, FeatureRequest, PublishedVersion};
+use crate::version::core::Version;
+use crate::version::requirement::VersionReq;
+
+/// Check that the features a manifest enables exist in the lowest version its
+/// requirement allows (what `-Z minimal-versions` resolves to). Features the
+/// latest release dropped are left to [`feature_changes`]. Releases whose
+/// feature table is unknown are given the benefit of the doubt.
+pub fn check_features(
+    request: &FeatureRequest,
+    versions: &[PublishedVersion],
+) -> Vec<FeatureProblem> {
+    let Some(requirement) = VersionReq::parse(&request.requirement) else {
+        return Vec::new();
+    };
+    // `default` always exists, and `dep/feature` entries are not features of this crate
+    let requested: Vec<&str> = request
+        .features
+        .iter()
+        .map(String::as_str)
+        .filter(|feature| *feature != "default" && !feature.contains('/'))
+        .collect();
+    let missing_from = |release: &PublishedVersion| -> Vec<String> {
+        let Some(offered) = &release.features else {
+            return Vec::new();
+        };
+        requested
+            .iter()
+            .filter(|feature| !offered.iter().any(|f| f == *feature))
+            .map(|feature| (*feature).to_string())
+            .collect()
+    };
+
+    let mut allowed: Vec<(Version, &PublishedVersion)> = versions
+        .iter()
+        .filter(|v| !v.yanked)
+        .filter_map(|v| Version::parse(&v.num).map(|parsed| (parsed, v)))
+        .filter(|(parsed, _)| requirement.matches(parsed))
+        .collect();
+    allowed.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut problems = Vec::new();
+    if let Some((_, lowest)) = allowed.first() {
+        let missing = missing_from(lowest);
+        if !missing.is_empty() {
+            problems.push(FeatureProblem::MissingAtLowerBound {
+                name: request.name.clone(),
+                source: request.source.clone(),
+                features: missing,
+                lowest: lowest.num.clone(),
+                tightened: allowed
+                    .iter()
+                    .find(|(_, v)| v.features.is_some() && missing_from(v).is_empty())
+                    .map(|(_, v)| v.num.clone()),
+            });
+        }
+    }
+    problems
+}
+
+/// Compare the feature tables of `current` and `latest`; `enabled` are the
+/// features the manifests turn on. `None` when either table is unknown.
+pub fn feature_changes(
+    current: &PublishedVersion,
+    latest: &PublishedVersion,
+    enabled: &[String],
+) -> Option<FeatureChanges> {
+    let (old, new) = (current.features.as_ref()?, latest.features.as_ref()?);
+    let (old_default, new_default) = (
+        current.default_features.as_ref()?,
+        latest.default_features.as_ref()?,
+    );
+    // `default` itself is compared through what it enables
+    let missing_from = |list: &[String], from: &[String]| -> Vec<String> {
+        list.iter()
+            .filter(|f| *f != "default" && !from.contains(f))
+            .cloned()
+            .collect()
+    };
+
+    Some(FeatureChanges {
+        from: current.num.clone(),
+        added: missing_from(new, old),
+        removed: missing_from(old, new),
+        default_added: missing_from(new_default, old_default),
+        default_removed: missing_from(old_default, new_default),
+        enabled_missing: enabled
+            .iter()
+            .filter(|f| *f != "default" && !f.contains('/') && !new.contains(f))
+            .cloned()
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(num: &str, features: &[&str]) -> PublishedVersion {
+        PublishedVersion {
+            num: num.to_string(),
+            features: Some(features.iter().map(|f| (*f).to_string()).collect()),
+            ..PublishedVersion::default()
+        }
+    }
+
+    #[test]
+    fn test_check_features() {
+        let versions = [
+            release("1.0.0", &["std"]),
+            release("1.0.30", &["std", "rc"]),
+            release("1.1.0", &["std", "rc", "alloc"]),
+            release("2.0.0", &["std", "alloc"]),
+        ];
+        let request = |requirement: &str, features: &[&str]| FeatureRequest {
+            name: "demo".to_string(),
+            requirement: requirement.to_string(),
+            features: features.iter().map(|f| (*f).to_string()).collect(),
+            source: "root".to_string(),
+        };
+
+        assert_eq!(
+            check_features(&request("1.0", &["rc", "default"]), &versions),
+            vec![FeatureProblem::MissingAtLowerBound {
+                name: "demo".to_string(),
+                source: "root".to_string(),
+                features: vec!["rc".to_string()],
+                lowest: "1.0.0".to_string(),
+                tightened: Some("1.0.30".to_string()),
+            },]
+        );
+        // Dropped by 2.0.0, which the requirement excludes: the feature diff's concern
+        assert!(check_features(&request("1.0.30", &["std", "rc"]), &versions).is_empty());
+        assert!(check_features(&request("2", &["alloc"]), &versions).is_empty());
+    }
+
+    #[test]
+    fn test_feature_changes() {
+        let mut current = release("1.1.0", &["std", "rc", "alloc"]);
+        current.default_features = Some(vec!["std".to_string()]);
+        let mut latest = release("2.0.0", &["std", "alloc", "serde"]);
+        latest.default_features = Some(vec!["std".to_string(), "alloc".to_string()]);
+
+        let changes = feature_changes(&current, &latest, &["rc".to_string()]).unwrap();
+        assert_eq!(changes.added, vec!["serde"]);
+        assert_eq!(changes.removed, vec!["rc"]);
+        assert_eq!(changes.default_added, vec!["alloc"]);
+        assert!(changes.default_removed.is_empty());
+        assert_eq!(changes.enabled_missing, vec!["rc"]);
+        assert!(changes.affects_build());
+        assert_eq!(feature_changes(&release("1.0.0", &[]), &latest, &[]), None);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod changelog;
 pub mod comparison;
 pub mod core;
+pub mod features;
+pub mod requirement;
@@ -0,0 +1,406 @@
+use std::cmp::Ordering;
+
+use crate::version::core::Version;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    major: u32,
+    minor: Option<u32>,
+    patch: Option<u32>,
+    pre_release: Option<String>,
+}
+
+/// One end of the versions a requirement allows, and whether it is included.
+type Bound = (Version, bool);
+
+/// A Cargo version requirement such as `1.2`, `^0.4.3`, `~1.2`, `>=1.0, <2` or `*`.
+///
+/// Matching follows Cargo's rules, including that a pre-release version only
+/// matches a requirement naming a pre-release of the same `major.minor.patch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(req: &str) -> Option<Self> {
+        let req = req.trim();
+        if req == "*" {
+            return Some(VersionReq {
+                comparators: Vec::new(),
+            });
+        }
+
+        let comparators = req
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(VersionReq { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        if !self.comparators.iter().all(|c| c.matches(version)) {
+            return false;
+        }
+        if version.pre_release.is_none() {
+            return true;
+        }
+        self.comparators.iter().any(|c| {
+            c.pre_release.is_some()
+                && c.major == version.major
+                && c.minor == Some(version.minor.unwrap_or(0))
+                && c.patch == Some(version.patch.unwrap_or(0))
+        })
+    }
+
+    /// `*` or only lower bounds (`>=1.0`): every newer release is already allowed.
+    pub fn is_unbounded(&self) -> bool {
+        self.comparators
+            .iter()
+            .all(|c| matches!(c.op, Op::Greater | Op::GreaterEq))
+    }
+
+    /// Exactly `*`: any version at all.
+    pub fn is_wildcard(&self) -> bool {
+        self.comparators.is_empty()
+    }
+
+    /// Whether a comparator pins an exact version (`=1.2.3`).
+    pub fn pins_exact(&self) -> bool {
+        self.comparators.iter().any(|c| c.op == Op::Exact)
+    }
+
+    /// Whether an explicit `<` or `<=` comparator caps the requirement.
+    pub fn has_upper_bound(&self) -> bool {
+        self.comparators
+            .iter()
+            .any(|c| matches!(c.op, Op::Less | Op::LessEq))
+    }
+
+    /// The lowest version the requirement names, e.g. `1.2.0` for `^1.2` or `>=1.2, <2`.
+    pub fn lower_bound(&self) -> Option<Version> {
+        self.comparators
+            .iter()
+            .filter(|c| !matches!(c.op, Op::Less | Op::LessEq))
+            .map(Comparator::lower_bound)
+            .max()
+    }
+
+    /// Whether the requirement already asks for `latest`: its lowest version is
+    /// `latest` or newer, or it names a version only as precisely as it cares
+    /// about (`1.2`, `^1` or `~1.2` for `1.2.7`) and `latest` fills in the rest.
+    pub fn is_current(&self, latest: &Version) -> bool {
+        if self.lower_bound().is_some_and(|lower| lower >= *latest) {
+            return true;
+        }
+        self.matches(latest)
+            && self.comparators.iter().all(|c| {
+                matches!(c.op, Op::Exact | Op::Tilde | Op::Caret | Op::Wildcard)
+                    && c.patch.is_none()
+                    && c.major == latest.major
+                    && c.minor
+                        .is_none_or(|minor| minor == latest.minor.unwrap_or(0))
+            })
+    }
+
+    /// Whether any comparator names a pre-release, e.g. `4.0.0-rc.3`.
+    pub fn names_prerelease(&self) -> bool {
+        self.comparators.iter().any(|c| c.pre_release.is_some())
+    }
+
+    /// Whether the string form of `version` satisfies this requirement.
+    pub fn matches_str(&self, version: &str) -> bool {
+        Version::parse(version).is_some_and(|v| self.matches(&v))
+    }
+
+    /// Whether both requirements allow the same versions, however they are
+    /// written: `1`, `1.0`, `^1` and `>=1.0.0, <2.0.0` are all equivalent.
+    pub fn is_equivalent(&self, other: &VersionReq) -> bool {
+        let prereleases = |req: &VersionReq| {
+            let mut named: Vec<Version> = req
+                .comparators
+                .iter()
+                .filter(|c| c.pre_release.is_some())
+                .map(|c| release(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)))
+                .collect();
+            named.sort();
+            named.dedup();
+            named
+        };
+        self.range() == other.range() && prereleases(self) == prereleases(other)
+    }
+
+    /// The comparators' ranges intersected: the highest lower bound and the lowest
+    /// upper bound, an excluded end beating an included one at the same version.
+    fn range(&self) -> (Bound, Option<Bound>) {
+        let mut lower = (release(0, 0, 0), true);
+        let mut upper: Option<Bound> = None;
+        for (low, high) in self.comparators.iter().map(Comparator::range) {
+            if (&low.0, !low.1) > (&lower.0, !lower.1) {
+                lower = low;
+            }
+            if let Some(high) = high
+                && upper
+                    .as_ref()
+                    .is_none_or(|upper| (&high.0, high.1) < (&upper.0, upper.1))
+            {
+                upper = Some(high);
+            }
+        }
+        (lower, upper)
+    }
+}
+
+fn release(major: u32, minor: u32, patch: u32) -> Version {
+    Version {
+        major,
+        minor: Some(minor),
+        patch: Some(patch),
+        pre_release: None,
+        build: None,
+    }
+}
+
+impl Comparator {
+    fn parse(part: &str) -> Option<Self> {
+        let part = part.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| part.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((Op::Caret, part));
+
+        let rest = rest.trim();
+        let rest = rest.split_once('+').map_or(rest, |(rest, _build)| rest);
+        let (core, pre_release) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (rest, None),
+        };
+
+        let mut parts = core.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let mut wildcard = false;
+        let mut next_part = |parts: &mut std::str::Split<'_, char>| -> Option<Option<u32>> {
+            match parts.next() {
+                None => Some(None),
+                Some("*" | "x" | "X") => {
+                    wildcard = true;
+                    Some(None)
+                }
+                Some(_) if wildcard => None,
+                Some(n) => n.parse().ok().map(Some),
+            }
+        };
+        let minor = next_part(&mut parts)?;
+        let patch = if minor.is_some() {
+            next_part(&mut parts)?
+        } else {
+            None
+        };
+        if parts.next().is_some() || (pre_release.is_some() && patch.is_none()) {
+            return None;
+        }
+
+        let op = if wildcard && matches!(op, Op::Caret | Op::Exact) {
+            Op::Wildcard
+        } else {
+            op
+        };
+
+        Some(Comparator {
+            op,
+            major,
+            minor,
+            patch,
+            pre_release,
+        })
+    }
+
+    fn lower_bound(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: Some(self.minor.unwrap_or(0)),
+            patch: Some(self.patch.unwrap_or(0)),
+            pre_release: self.pre_release.clone(),
+            build: None,
+        }
+    }
+
+    /// The versions `matches` accepts, pre-release rules aside, as bounds.
+    fn range(&self) -> (Bound, Option<Bound>) {
+        let lower = self.lower_bound();
+        // The first release past the named major, or major.minor when given
+        let past = match self.minor {
+            None => release(self.major + 1, 0, 0),
+            Some(minor) => release(self.major, minor + 1, 0),
+        };
+        let full = self.patch.is_some();
+        let floor = (release(0, 0, 0), true);
+        match self.op {
+            Op::Exact | Op::Wildcard if full => ((lower.clone(), true), Some((lower, true))),
+            Op::Exact | Op::Wildcard | Op::Tilde => ((lower, true), Some((past, false))),
+            Op::Greater if full => ((lower, false), None),
+            Op::Greater => ((past, true), None),
+            Op::GreaterEq => ((lower, true), None),
+            Op::Less => (floor, Some((lower, false))),
+            Op::LessEq if full => (floor, Some((lower, true))),
+            Op::LessEq => (floor, Some((past, false))),
+            Op::Caret => {
+                let upper = match (self.major, self.minor, self.patch) {
+                    (0, Some(0), Some(patch)) => release(0, 0, patch + 1),
+                    (0, Some(minor), _) => release(0, minor + 1, 0),
+                    (major, _, _) => release(major + 1, 0, 0),
+                };
+                ((lower, true), Some((upper, false)))
+            }
+        }
+    }
+
+    /// Compare `version` against this comparator, only on the components it specifies.
+    fn cmp_partial(&self, version: &Version) -> Ordering {
+        let major = version.major.cmp(&self.major);
+        let Some(minor) = self.minor else {
+            return major;
+        };
+        let minor = major.then(version.minor.unwrap_or(0).cmp(&minor));
+        if self.patch.is_none() {
+            return minor;
+        }
+        version.cmp(&self.lower_bound())
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let v_minor = version.minor.unwrap_or(0);
+        match self.op {
+            Op::Exact | Op::Wildcard => self.cmp_partial(version) == Ordering::Equal,
+            Op::Greater => self.cmp_partial(version) == Ordering::Greater,
+            Op::GreaterEq => self.cmp_partial(version) != Ordering::Less,
+            Op::Less => self.cmp_partial(version) == Ordering::Less,
+            Op::LessEq => self.cmp_partial(version) != Ordering::Greater,
+            Op::Tilde => {
+                version.major == self.major
+                    && self.minor.is_none_or(|minor| v_minor == minor)
+                    && *version >= self.lower_bound()
+            }
+            Op::Caret => {
+                if *version < self.lower_bound() || version.major != self.major {
+                    return false;
+                }
+                // Below 1.0 the leftmost non-zero component is the compatibility boundary
+                match (self.major, self.minor, self.patch) {
+                    (0, Some(0), Some(patch)) => {
+                        v_minor == 0 && version.patch.unwrap_or(0) == patch
+                    }
+                    (0, Some(minor), _) => v_minor == minor,
+                    _ => true,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(req: &str, version: &str) -> bool {
+        VersionReq::parse(req).unwrap().matches_str(version)
+    }
+
+    #[test]
+    fn test_caret_requirements() {
+        assert!(matches("1.2.3", "1.2.3"));
+        assert!(matches("1.2.3", "1.9.0"));
+        assert!(!matches("1.2.3", "1.2.2"));
+        assert!(!matches("1.2.3", "2.0.0"));
+        assert!(matches("^0.4", "0.4.27"));
+        assert!(!matches("0.4", "0.5.0"));
+        assert!(matches("0", "0.9.9"));
+        assert!(matches("0.0.3", "0.0.3"));
+        assert!(!matches("0.0.3", "0.0.4"));
+    }
+
+    #[test]
+    fn test_operator_requirements() {
+        assert!(matches("~1.2", "1.2.9"));
+        assert!(!matches("~1.2.3", "1.3.0"));
+        assert!(matches("=1.2.3", "1.2.3"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches(">=1.0, <1.5", "1.4.9"));
+        assert!(!matches(">=1.0, <1.5", "1.5.0"));
+        assert!(matches("<=1.2", "1.2.7"));
+        assert!(matches(">1", "2.0.0"));
+        assert!(!matches(">1", "1.9.0"));
+        assert!(matches("*", "3.1.4"));
+        assert!(matches("1.*", "1.7.0"));
+        assert!(!matches("1.2.*", "1.3.0"));
+    }
+
+    #[test]
+    fn test_prerelease_requirements() {
+        assert!(!matches("1.0", "1.1.0-beta.1"));
+        assert!(matches("4.0.0-rc.3", "4.0.0-rc.5"));
+        assert!(matches("4.0.0-rc.3", "4.0.0"));
+        assert!(!matches("4.0.0-rc.3", "4.1.0-rc.1"));
+        assert!(matches("1.1.8", "1.1.8+spec-1.1.0"));
+    }
+
+    #[test]
+    fn test_bounds() {
+        assert!(VersionReq::parse("*").unwrap().is_unbounded());
+        assert!(VersionReq::parse(">=1.0").unwrap().is_unbounded());
+        assert!(!VersionReq::parse(">=1.0, <2").unwrap().is_unbounded());
+        assert_eq!(
+            VersionReq::parse(">=1.2, <2").unwrap().lower_bound(),
+            Version::parse("1.2.0")
+        );
+        assert_eq!(VersionReq::parse("<2").unwrap().lower_bound(), None);
+    }
+
+    #[test]
+    fn test_equivalent_requirements() {
+        let equivalent = |a: &str, b: &str| {
+            VersionReq::parse(a)
+                .unwrap()
+                .is_equivalent(&VersionReq::parse(b).unwrap())
+        };
+        assert!(equivalent("1", "1.0"));
+        assert!(equivalent("1", "^1.0.0"));
+        assert!(equivalent("1.0", ">=1.0.0, <2.0.0"));
+        assert!(equivalent("~1.2", "1.2.*"));
+        assert!(equivalent("=1.2.3", ">=1.2.3, <=1.2.3"));
+        assert!(equivalent("*", ">=0"));
+        assert!(!equivalent("0", "0.0"));
+        assert!(!equivalent("1", "1.0.1"));
+        assert!(!equivalent("~1.2.3", "1.2.3"));
+        assert!(!equivalent(">1.2.3", ">=1.2.3"));
+        assert!(!equivalent("4.0.0-rc.3", "4.0.0-rc.4"));
+    }
+
+    #[test]
+    fn test_invalid_requirements() {
+        assert!(VersionReq::parse("latest").is_none());
+        assert!(VersionReq::parse("1.*.3").is_none());
+        assert!(VersionReq::parse("").is_none());
+    }
+}